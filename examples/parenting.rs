@@ -11,7 +11,7 @@ fn main() {
                 }),
                 ..default()
             }),
-            TransformGizmoPlugin,
+            TransformGizmoPlugin::default(),
         ))
         .add_systems(Startup, setup)
         .run();
@@ -0,0 +1,259 @@
+use std::sync::Arc;
+
+use bevy::prelude::*;
+
+use crate::{GizmoAxis, GizmoHandle};
+
+/// Restricts the axes a [`GizmoTransformable`](crate::GizmoTransformable) Entity can be manipulated along.
+///
+/// Axes are in the space of the Entity's parent, except for scale, which always uses the Entity's own axes.
+/// Handles for locked axes are hidden while the Entity is selected.
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct GizmoConstraints {
+    pub translation: BVec3,
+    pub rotation: BVec3,
+    pub scale: BVec3,
+}
+
+impl Default for GizmoConstraints {
+    fn default() -> Self {
+        GizmoConstraints {
+            translation: BVec3::TRUE,
+            rotation: BVec3::TRUE,
+            scale: BVec3::TRUE,
+        }
+    }
+}
+
+impl GizmoConstraints {
+    /// Allows nothing, combine with the `with_*` methods to unlock axes.
+    pub const LOCKED: Self = GizmoConstraints {
+        translation: BVec3::FALSE,
+        rotation: BVec3::FALSE,
+        scale: BVec3::FALSE,
+    };
+
+    pub fn with_translation(mut self, axes: BVec3) -> Self {
+        self.translation = axes;
+        self
+    }
+    pub fn with_rotation(mut self, axes: BVec3) -> Self {
+        self.rotation = axes;
+        self
+    }
+    pub fn with_scale(mut self, axes: BVec3) -> Self {
+        self.scale = axes;
+        self
+    }
+
+    /// Returns whether `handle` can move the Entity at all.
+    pub fn allows(&self, handle: GizmoHandle) -> bool {
+        match handle {
+            GizmoHandle::TranslateAxis(axis) => axis_allowed(self.translation, axis),
+            GizmoHandle::TranslatePlane(normal) => GizmoAxis::ALL
+                .into_iter()
+                .filter(|axis| *axis != normal)
+                .all(|axis| axis_allowed(self.translation, axis)),
+            GizmoHandle::TranslateView => self.translation.any(),
+            GizmoHandle::RotateAxis(axis) => axis_allowed(self.rotation, axis),
+            GizmoHandle::RotateView | GizmoHandle::RotateFree => self.rotation.any(),
+            GizmoHandle::ScaleAxis(axis) => axis_allowed(self.scale, axis),
+            GizmoHandle::ScaleUniform => self.scale.all(),
+        }
+    }
+
+    /// Removes the locked components of a translation delta.
+    pub fn constrain_translation(&self, delta: Vec3) -> Vec3 {
+        delta * mask(self.translation)
+    }
+    /// Removes the locked components of a rotation axis, the result is zero if no component is left.
+    pub fn constrain_rotation_axis(&self, axis: Vec3) -> Vec3 {
+        (axis * mask(self.rotation)).normalize_or_zero()
+    }
+    /// Removes the locked components of a rotation.
+    pub fn constrain_rotation(&self, rotation: Quat) -> Quat {
+        Quat::from_scaled_axis(rotation.to_scaled_axis() * mask(self.rotation))
+    }
+    /// Removes the locked components of a scale delta.
+    pub fn constrain_scale(&self, delta: Vec3) -> Vec3 {
+        delta * mask(self.scale)
+    }
+}
+
+fn axis_allowed(axes: BVec3, axis: GizmoAxis) -> bool {
+    match axis {
+        GizmoAxis::X => axes.x,
+        GizmoAxis::Y => axes.y,
+        GizmoAxis::Z => axes.z,
+    }
+}
+
+fn mask(axes: BVec3) -> Vec3 {
+    Vec3::select(axes, Vec3::ONE, Vec3::ZERO)
+}
+
+/// Region the translation of a [`GizmoLimits`] Entity is clamped to.
+#[derive(Clone)]
+pub enum TranslationLimit {
+    /// Axis aligned box in the space of the Entity's parent.
+    Aabb { min: Vec3, max: Vec3 },
+    /// Function returning the closest allowed translation to the proposed one.
+    Custom(Arc<dyn Fn(Vec3) -> Vec3 + Send + Sync>),
+}
+
+impl std::fmt::Debug for TranslationLimit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TranslationLimit::Aabb { min, max } => {
+                f.debug_struct("Aabb").field("min", min).field("max", max).finish()
+            }
+            TranslationLimit::Custom(_) => f.write_str("Custom"),
+        }
+    }
+}
+
+/// Clamps the transform of a [`GizmoTransformable`](crate::GizmoTransformable) Entity while it is manipulated.
+///
+/// Limits are applied to the local transform as it is dragged, so the handles stop at the limit instead of
+/// the Entity being moved back afterwards.
+#[derive(Component, Debug, Clone, Default)]
+pub struct GizmoLimits {
+    pub translation: Option<TranslationLimit>,
+    /// Minimum and maximum XYZ Euler angles in radians, see [`GizmoLimits::clamp_rotation`].
+    pub rotation: Option<(Vec3, Vec3)>,
+    /// Minimum and maximum scale.
+    pub scale: Option<(Vec3, Vec3)>,
+}
+
+impl GizmoLimits {
+    pub fn with_translation_aabb(mut self, min: Vec3, max: Vec3) -> Self {
+        self.translation = Some(TranslationLimit::Aabb { min, max });
+        self
+    }
+    pub fn with_translation_region(mut self, region: impl Fn(Vec3) -> Vec3 + Send + Sync + 'static) -> Self {
+        self.translation = Some(TranslationLimit::Custom(Arc::new(region)));
+        self
+    }
+    pub fn with_rotation(mut self, min: Vec3, max: Vec3) -> Self {
+        self.rotation = Some((min, max));
+        self
+    }
+    pub fn with_scale(mut self, min: Vec3, max: Vec3) -> Self {
+        self.scale = Some((min, max));
+        self
+    }
+
+    pub fn clamp_translation(&self, translation: Vec3) -> Vec3 {
+        match &self.translation {
+            Some(TranslationLimit::Aabb { min, max }) => translation.clamp(*min, *max),
+            Some(TranslationLimit::Custom(region)) => region(translation),
+            None => translation,
+        }
+    }
+    /// Clamps each XYZ Euler angle of `rotation` on its own.
+    ///
+    /// The angles are in `-π..=π` (`-π/2..=π/2` for Y), so a range can't cross ±π. Suited to limits on one or two
+    /// axes, with several axes rotated far the decomposition can flip and the clamped rotation jump.
+    pub fn clamp_rotation(&self, rotation: Quat) -> Quat {
+        let Some((min, max)) = self.rotation else {
+            return rotation;
+        };
+        let (x, y, z) = rotation.to_euler(EulerRot::XYZ);
+        let clamped = Vec3::new(x, y, z).clamp(min, max);
+        Quat::from_euler(EulerRot::XYZ, clamped.x, clamped.y, clamped.z)
+    }
+    pub fn clamp_scale(&self, scale: Vec3) -> Vec3 {
+        match self.scale {
+            Some((min, max)) => scale.clamp(min, max),
+            None => scale,
+        }
+    }
+    /// Clamps every part of `transform`.
+    pub fn clamp(&self, transform: Transform) -> Transform {
+        Transform {
+            translation: self.clamp_translation(transform.translation),
+            rotation: self.clamp_rotation(transform.rotation),
+            scale: self.clamp_scale(transform.scale),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::FRAC_PI_2;
+
+    use super::*;
+
+    #[test]
+    fn allows_handles_of_unlocked_axes() {
+        let constraints = GizmoConstraints::LOCKED.with_translation(BVec3::new(true, false, true));
+        assert!(constraints.allows(GizmoHandle::TranslateAxis(GizmoAxis::X)));
+        assert!(!constraints.allows(GizmoHandle::TranslateAxis(GizmoAxis::Y)));
+        // A plane needs both of its axes
+        assert!(constraints.allows(GizmoHandle::TranslatePlane(GizmoAxis::Y)));
+        assert!(!constraints.allows(GizmoHandle::TranslatePlane(GizmoAxis::X)));
+        assert!(constraints.allows(GizmoHandle::TranslateView));
+        assert!(!constraints.allows(GizmoHandle::RotateView));
+        assert!(!constraints.allows(GizmoHandle::ScaleAxis(GizmoAxis::X)));
+
+        let scale = GizmoConstraints::LOCKED.with_scale(BVec3::new(true, true, false));
+        assert!(scale.allows(GizmoHandle::ScaleAxis(GizmoAxis::Y)));
+        assert!(!scale.allows(GizmoHandle::ScaleUniform));
+        assert!(GizmoConstraints::default().allows(GizmoHandle::ScaleUniform));
+    }
+
+    #[test]
+    fn removes_locked_components() {
+        let constraints = GizmoConstraints::LOCKED
+            .with_translation(BVec3::new(true, false, false))
+            .with_rotation(BVec3::new(false, true, false))
+            .with_scale(BVec3::new(false, false, true));
+        assert_eq!(constraints.constrain_translation(Vec3::new(1.0, 2.0, 3.0)), Vec3::new(1.0, 0.0, 0.0));
+        assert_eq!(constraints.constrain_scale(Vec3::new(1.0, 2.0, 3.0)), Vec3::new(0.0, 0.0, 3.0));
+        assert!(constraints.constrain_rotation_axis(Vec3::new(1.0, 1.0, 0.0)).abs_diff_eq(Vec3::Y, 1e-6));
+        assert_eq!(constraints.constrain_rotation_axis(Vec3::X), Vec3::ZERO);
+    }
+
+    #[test]
+    fn removes_locked_rotation() {
+        let constraints = GizmoConstraints::LOCKED.with_rotation(BVec3::new(false, true, false));
+        let rotation = Quat::from_rotation_y(0.5);
+        assert!(constraints.constrain_rotation(rotation).abs_diff_eq(rotation, 1e-5));
+        assert!(constraints.constrain_rotation(Quat::from_rotation_x(0.5)).abs_diff_eq(Quat::IDENTITY, 1e-5));
+    }
+
+    #[test]
+    fn clamps_translation_to_aabb() {
+        let limits = GizmoLimits::default().with_translation_aabb(Vec3::splat(-1.0), Vec3::splat(1.0));
+        assert_eq!(limits.clamp_translation(Vec3::new(2.0, -3.0, 0.5)), Vec3::new(1.0, -1.0, 0.5));
+    }
+
+    #[test]
+    fn clamps_translation_to_region() {
+        let limits = GizmoLimits::default().with_translation_region(|translation| translation.with_y(0.0));
+        assert_eq!(limits.clamp_translation(Vec3::new(1.0, 2.0, 3.0)), Vec3::new(1.0, 0.0, 3.0));
+    }
+
+    #[test]
+    fn clamps_scale() {
+        let limits = GizmoLimits::default().with_scale(Vec3::splat(0.5), Vec3::splat(2.0));
+        assert_eq!(limits.clamp_scale(Vec3::new(0.1, 1.0, 4.0)), Vec3::new(0.5, 1.0, 2.0));
+    }
+
+    #[test]
+    fn clamps_rotation_per_axis() {
+        let limits = GizmoLimits::default().with_rotation(Vec3::new(-FRAC_PI_2, 0.0, 0.0), Vec3::new(FRAC_PI_2, 0.0, 0.0));
+        let inside = Quat::from_rotation_x(0.5);
+        assert!(limits.clamp_rotation(inside).abs_diff_eq(inside, 1e-5));
+        let clamped = limits.clamp_rotation(Quat::from_rotation_x(2.0));
+        assert!(clamped.abs_diff_eq(Quat::from_rotation_x(FRAC_PI_2), 1e-5));
+        // Rotation about a locked axis is removed
+        assert!(limits.clamp_rotation(Quat::from_rotation_y(0.5)).abs_diff_eq(Quat::IDENTITY, 1e-5));
+    }
+
+    #[test]
+    fn unlimited_transform_is_unchanged() {
+        let transform = Transform::from_xyz(1.0, 2.0, 3.0).with_rotation(Quat::from_rotation_z(1.0)).with_scale(Vec3::splat(2.0));
+        assert_eq!(GizmoLimits::default().clamp(transform), transform);
+    }
+}
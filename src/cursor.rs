@@ -0,0 +1,181 @@
+use bevy::{
+    picking::{
+        hover::HoverMap,
+        mesh_picking::ray_cast::{MeshRayCast, MeshRayCastSettings},
+        pointer::{PointerAction, PointerInput},
+    },
+    prelude::*,
+    window::PrimaryWindow,
+};
+
+use crate::{
+    constraints::{GizmoConstraints, GizmoLimits},
+    input::GizmoInputMap,
+    parent_transform, viewport_position, GizmoDrag, GizmoDragStarted, GizmoPickSource, TransformGizmoPart,
+    TransformGizmoSettings,
+};
+
+/// A 3D cursor that marks a point in the scene, like the one in Blender.
+///
+/// The plugin spawns one hidden cursor at the world origin, set [`GizmoCursor::visible`] to draw it. It is placed by
+/// clicking on scene geometry with the [`GizmoInputMap::place_cursor_button`], or by setting its [`Transform`]
+/// directly. New objects can be spawned at its [`GlobalTransform`], and it serves as the pivot for rotation and scale
+/// with [`GizmoPivot::Cursor`].
+#[derive(Component, Debug, Clone, Copy)]
+pub struct GizmoCursor {
+    /// Whether the cursor widget is drawn.
+    pub visible: bool,
+    pub color: Color,
+}
+
+impl Default for GizmoCursor {
+    fn default() -> Self {
+        GizmoCursor {
+            visible: false,
+            color: Color::srgb(0.95, 0.95, 0.95),
+        }
+    }
+}
+
+/// Point the selected Entity is rotated and scaled around.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum GizmoPivot {
+    /// The origin of the selected Entity.
+    #[default]
+    Origin,
+    /// The [`GizmoCursor`].
+    Cursor,
+}
+
+/// Moves the selected Entity to the [`GizmoCursor`].
+#[derive(Event, Debug, Default, Clone, Copy)]
+pub struct MoveSelectionToCursor;
+
+/// Startup system that spawns the [`GizmoCursor`].
+pub fn spawn_cursor(mut commands: Commands) {
+    commands.spawn((
+        Name::new("Gizmo Cursor"),
+        GizmoCursor::default(),
+        Transform::default(),
+    ));
+}
+
+/// Places the [`GizmoCursor`] on the scene geometry under the pointer when the
+/// [`GizmoInputMap::place_cursor_button`] is pressed.
+///
+/// Casts its own ray against the scene meshes, so it works with any picking backend.
+pub fn place_cursor(
+    mut pointer_input: EventReader<PointerInput>,
+    keys: Res<ButtonInput<KeyCode>>,
+    input_map: Res<GizmoInputMap>,
+    hover_map: Res<HoverMap>,
+    primary_window: Query<Entity, With<PrimaryWindow>>,
+    q_camera: Query<(&Camera, &GlobalTransform), With<GizmoPickSource>>,
+    q_parts: Query<(), With<TransformGizmoPart>>,
+    mut ray_cast: MeshRayCast,
+    mut q_cursor: Query<&mut Transform, With<GizmoCursor>>,
+) {
+    for input in pointer_input.read() {
+        if !matches!(input.action, PointerAction::Press(button) if button == input_map.place_cursor_button)
+            || !input_map.place_cursor_modifier_pressed(&keys)
+        {
+            continue;
+        }
+        // Presses on the gizmo belong to its handles
+        let on_gizmo = hover_map
+            .get(&input.pointer_id)
+            .is_some_and(|hovered| hovered.keys().any(|entity| q_parts.contains(*entity)));
+        let Ok((camera, camera_transform)) = q_camera.single() else {
+            continue;
+        };
+        if on_gizmo || !input.location.is_in_viewport(camera, &primary_window) {
+            continue;
+        }
+        let Ok(ray) = camera.viewport_to_world(camera_transform, viewport_position(camera, &input.location)) else {
+            continue;
+        };
+        let filter = |entity: Entity| !q_parts.contains(entity);
+        let ray_cast_settings = MeshRayCastSettings::default().with_filter(&filter);
+        let Some((_, hit)) = ray_cast.cast_ray(ray, &ray_cast_settings).first() else {
+            continue;
+        };
+        for mut cursor_transform in &mut q_cursor {
+            cursor_transform.translation = hit.point;
+        }
+    }
+}
+
+/// Returns the position of the [`GizmoCursor`] in the space of the parent of `entity`.
+pub fn cursor_in_parent_space(
+    entity: Entity,
+    q_cursor: &Query<&GlobalTransform, With<GizmoCursor>>,
+    q_parents: &Query<&ChildOf>,
+    q_transform: &Query<&GlobalTransform>,
+) -> Option<Vec3> {
+    let cursor = q_cursor.iter().next()?.translation();
+    Some(parent_transform(entity, q_parents, q_transform).map_or(cursor, |parent| parent.affine().inverse().transform_point3(cursor)))
+}
+
+/// Proposes moving the selected Entity to the [`GizmoCursor`] when [`MoveSelectionToCursor`] is sent.
+///
+/// The move is a drag that finishes at once, so it sends the same drag events as a pointer drag.
+pub fn move_selection_to_cursor(
+    mut events: EventReader<MoveSelectionToCursor>,
+    mut settings: ResMut<TransformGizmoSettings>,
+    mut drag_started: EventWriter<GizmoDragStarted>,
+    q_cursor: Query<&GlobalTransform, With<GizmoCursor>>,
+    q_parents: Query<&ChildOf>,
+    q_transform: Query<&GlobalTransform>,
+    q_local_transform: Query<&Transform>,
+    q_constraints: Query<&GizmoConstraints>,
+    q_limits: Query<&GizmoLimits>,
+) {
+    if events.read().count() == 0 || settings.drag.is_some() {
+        return;
+    }
+    let Some(sel_entity) = settings.active_entity else {
+        return;
+    };
+    let Some(target) = cursor_in_parent_space(sel_entity, &q_cursor, &q_parents, &q_transform) else {
+        return;
+    };
+    let Ok(selection_transform_local) = q_local_transform.get(sel_entity) else {
+        log::warn!("TransformGizmo: Could not get Transform of selected Entity: {sel_entity:?}");
+        return;
+    };
+    let constraints = q_constraints.get(sel_entity).copied().unwrap_or_default();
+    settings.propose(sel_entity, *selection_transform_local, |proposal| {
+        proposal.translation += constraints.constrain_translation(target - proposal.translation);
+        if let Ok(limits) = q_limits.get(sel_entity) {
+            proposal.translation = limits.clamp_translation(proposal.translation);
+        }
+    });
+    // finish_drag sends GizmoDragEnded once the proposal is committed
+    settings.drag = Some(GizmoDrag {
+        finished: true,
+        ..GizmoDrag::new(*selection_transform_local)
+    });
+    drag_started.write(GizmoDragStarted {
+        entity: sel_entity,
+        transform: *selection_transform_local,
+    });
+}
+
+/// Draws the [`GizmoCursor`] as a camera facing ring with its axes.
+pub fn draw_cursor(
+    q_cursor: Query<(&GizmoCursor, &GlobalTransform)>,
+    q_camera: Query<&GlobalTransform, With<GizmoPickSource>>,
+    mut gizmos: Gizmos,
+) {
+    let camera_rotation = q_camera
+        .single()
+        .map_or(Quat::IDENTITY, |camera_transform| camera_transform.rotation());
+    for (cursor, cursor_transform) in &q_cursor {
+        if !cursor.visible {
+            continue;
+        }
+        let position = cursor_transform.translation();
+        gizmos.circle(Isometry3d::new(position, camera_rotation), 0.15, cursor.color);
+        gizmos.axes(Transform::from_translation(position), 0.25);
+    }
+}
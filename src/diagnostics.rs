@@ -0,0 +1,177 @@
+use bevy::{
+    diagnostic::{Diagnostic, DiagnosticPath, Diagnostics, RegisterDiagnostic},
+    picking::{hover::HoverMap, pointer::PointerLocation},
+    platform::time::Instant,
+    prelude::*,
+    window::PrimaryWindow,
+};
+
+use crate::{viewport_position, GizmoHandle, GizmoPickSource, TransformGizmoSet, TransformGizmoSettings};
+
+/// Adds an on-screen overlay of the gizmo state and [`Diagnostic`]s for the time its systems take.
+///
+/// Add it next to the [`TransformGizmoPlugin`](crate::TransformGizmoPlugin) while debugging. It draws where the
+/// pointer ray hits the plane of the handle under the pointer, and the overlay lists the selected Entity, the drag
+/// state and that handle. Without the `ui` feature the overlay is logged at debug level instead.
+#[derive(Debug, Default, Clone)]
+pub struct GizmoDiagnosticsPlugin;
+
+impl GizmoDiagnosticsPlugin {
+    /// Wall time from the first to the last gizmo system in [`Update`], in milliseconds.
+    pub const UPDATE_TIME: DiagnosticPath = DiagnosticPath::const_new("transform_gizmo/update_time");
+    /// Wall time of placing the gizmo in [`PostUpdate`], in milliseconds.
+    pub const PLACEMENT_TIME: DiagnosticPath = DiagnosticPath::const_new("transform_gizmo/placement_time");
+}
+
+impl Plugin for GizmoDiagnosticsPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_diagnostic(Diagnostic::new(Self::UPDATE_TIME).with_suffix("ms"));
+        app.register_diagnostic(Diagnostic::new(Self::PLACEMENT_TIME).with_suffix("ms"));
+        app.init_resource::<GizmoDiagnosticsTimer>();
+
+        #[cfg(feature = "ui")]
+        app.add_systems(Startup, spawn_overlay);
+        app.add_systems(Update, (
+            start_update_timer.before(TransformGizmoSet::Input),
+            measure_update_time.after(TransformGizmoSet::Render),
+            update_overlay,
+        ));
+        app.add_systems(PostUpdate, (
+            start_placement_timer.before(TransformGizmoSet::Sync),
+            measure_placement_time.after(TransformGizmoSet::Render),
+        ));
+    }
+}
+
+/// Text in the top left corner of the window showing the gizmo state.
+#[derive(Component)]
+pub struct GizmoDiagnosticsOverlay;
+
+/// Start of the gizmo systems in the current schedule.
+#[derive(Resource, Debug, Default)]
+struct GizmoDiagnosticsTimer {
+    update: Option<Instant>,
+    placement: Option<Instant>,
+}
+
+fn start_update_timer(mut timer: ResMut<GizmoDiagnosticsTimer>) {
+    timer.update = Some(Instant::now());
+}
+
+fn measure_update_time(mut timer: ResMut<GizmoDiagnosticsTimer>, mut diagnostics: Diagnostics) {
+    if let Some(start) = timer.update.take() {
+        diagnostics.add_measurement(&GizmoDiagnosticsPlugin::UPDATE_TIME, || start.elapsed().as_secs_f64() * 1000.0);
+    }
+}
+
+fn start_placement_timer(mut timer: ResMut<GizmoDiagnosticsTimer>) {
+    timer.placement = Some(Instant::now());
+}
+
+fn measure_placement_time(mut timer: ResMut<GizmoDiagnosticsTimer>, mut diagnostics: Diagnostics) {
+    if let Some(start) = timer.placement.take() {
+        diagnostics.add_measurement(&GizmoDiagnosticsPlugin::PLACEMENT_TIME, || start.elapsed().as_secs_f64() * 1000.0);
+    }
+}
+
+#[cfg(feature = "ui")]
+fn spawn_overlay(mut commands: Commands) {
+    commands.spawn((
+        Name::new("Gizmo Diagnostics"),
+        Text::new(""),
+        TextFont {
+            font_size: 14.0,
+            ..default()
+        },
+        TextColor(Color::WHITE),
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(8.0),
+            left: Val::Px(8.0),
+            ..default()
+        },
+        Pickable::IGNORE,
+        GizmoDiagnosticsOverlay,
+    ));
+}
+
+/// Normal of the plane the drag observers intersect the pointer ray with, `None` for screen space handles.
+fn drag_plane_normal(handle: GizmoHandle, handle_transform: &GlobalTransform, camera_transform: &GlobalTransform) -> Option<Dir3> {
+    match handle {
+        GizmoHandle::TranslateAxis(_) | GizmoHandle::ScaleAxis(_) => Some(handle_transform.forward()),
+        GizmoHandle::TranslatePlane(_) | GizmoHandle::RotateAxis(_) | GizmoHandle::RotateView => {
+            Some(handle_transform.up())
+        }
+        GizmoHandle::TranslateView => Some(camera_transform.back()),
+        GizmoHandle::RotateFree | GizmoHandle::ScaleUniform => None,
+    }
+}
+
+/// Updates the [`GizmoDiagnosticsOverlay`] and draws the intersection of the pointer ray with the drag plane.
+fn update_overlay(
+    settings: Res<TransformGizmoSettings>,
+    hover_map: Res<HoverMap>,
+    q_pointers: Query<&PointerLocation>,
+    primary_window: Query<Entity, With<PrimaryWindow>>,
+    q_camera: Query<(&Camera, &GlobalTransform), With<GizmoPickSource>>,
+    q_handles: Query<(Entity, &GizmoHandle, &GlobalTransform)>,
+    #[cfg(feature = "ui")] mut q_overlay: Query<&mut Text, With<GizmoDiagnosticsOverlay>>,
+    #[cfg(not(feature = "ui"))] mut logged: Local<String>,
+    mut gizmos: Gizmos,
+) {
+    let hovered = hover_map
+        .values()
+        .flat_map(|hovered| hovered.keys())
+        .find_map(|entity| q_handles.get(*entity).ok());
+    let inspected = settings
+        .active_handle
+        .and_then(|entity| q_handles.get(entity).ok())
+        .or(hovered);
+
+    let intersection = inspected.and_then(|(_, handle, handle_transform)| {
+        let (camera, camera_transform) = q_camera.single().ok()?;
+        let location = q_pointers
+            .iter()
+            .filter_map(PointerLocation::location)
+            .find(|location| location.is_in_viewport(camera, &primary_window))?;
+        let position = viewport_position(camera, location);
+        let ray = camera.viewport_to_world(camera_transform, position).ok()?;
+        let normal = drag_plane_normal(*handle, handle_transform, camera_transform)?;
+        let origin = handle_transform.translation();
+        let distance = ray.intersect_plane(origin, InfinitePlane3d::new(normal))?;
+        Some((origin, normal, ray.get_point(distance)))
+    });
+
+    if let Some((origin, normal, point)) = intersection {
+        let color = Color::srgb(1.0, 0.0, 1.0);
+        gizmos.rect(Isometry3d::new(origin, Quat::from_rotation_arc(Vec3::Z, *normal)), Vec2::splat(1.0), color);
+        gizmos.line(origin, point, color);
+        gizmos.sphere(Isometry3d::from_translation(point), 0.03, color);
+    }
+
+    let overlay = format!(
+        "Active entity: {:?}  Origin: {:?}\nMode: {:?}  Orientation: {:?}  Pivot: {:?}\nDragging: {}  Active handle: {:?}\nHovered handle: {:?}\nProposal rejected: {}\nIntersection: {:?}",
+        settings.active_entity,
+        settings.origin.map(|origin| origin.translation()),
+        settings.mode,
+        settings.orientation,
+        settings.pivot,
+        settings.is_dragging,
+        settings.active_handle.and_then(|entity| q_handles.get(entity).ok()).map(|(_, handle, _)| *handle),
+        hovered.map(|(_, handle, _)| *handle),
+        settings.rejected,
+        intersection.map(|(_, _, point)| point),
+    );
+    #[cfg(feature = "ui")]
+    for mut text in &mut q_overlay {
+        if text.0 != overlay {
+            text.0.clone_from(&overlay);
+        }
+    }
+    // Without bevy_ui the overlay goes to the log, only when it changes
+    #[cfg(not(feature = "ui"))]
+    if *logged != overlay {
+        log::debug!("TransformGizmo:\n{overlay}");
+        *logged = overlay;
+    }
+}
@@ -0,0 +1,194 @@
+use bevy::{prelude::*, render::primitives::Aabb};
+
+use std::f32::consts::{PI, TAU};
+
+use crate::{mesh::sector::Sector, parent_transform, GizmoMode, TransformGizmoSettings};
+
+/// Angle swept by a rotation drag, recorded on the first drag event.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct RotationSector {
+    /// World space center of the rotation ring.
+    pub(crate) origin: Vec3,
+    /// World space axis of the rotation ring.
+    pub(crate) axis: Vec3,
+    /// World space direction from the origin to where the drag started.
+    pub(crate) start: Vec3,
+    /// Radius of the dragged ring.
+    pub(crate) radius: f32,
+    /// Signed angle the committed rotation of the Entity turned around the axis so far.
+    pub(crate) angle: f32,
+}
+
+impl RotationSector {
+    /// Rotation that maps the +X start and +Y axis of a [`Sector`] mesh onto this sector.
+    fn rotation(&self) -> Quat {
+        Quat::from_mat3(&Mat3::from_cols(self.start, self.axis, self.start.cross(self.axis)))
+    }
+}
+
+/// Translucent pie slice showing the angle of the current rotation drag.
+#[derive(Component)]
+pub struct GizmoRotationSector;
+
+/// Text next to the gizmo showing the amount of the current manipulation, spawned with the `ui` feature.
+#[derive(Component)]
+pub struct GizmoFeedbackLabel;
+
+/// Color of the ghost of the original pose and the line to the current one.
+const GHOST_COLOR: Color = Color::srgba(1.0, 1.0, 1.0, 0.4);
+
+/// Returns the world transform of the selected Entity when the current drag started, and its current world position.
+fn translation_feedback(
+    settings: &TransformGizmoSettings,
+    q_transform: &Query<&GlobalTransform>,
+    q_parents: &Query<&ChildOf>,
+) -> Option<(Entity, GlobalTransform, Vec3)> {
+    let drag = settings
+        .drag
+        .filter(|drag| !drag.cancelled && drag.mode == Some(GizmoMode::Translate) && settings.is_dragging())?;
+    let sel_entity = settings.active_entity?;
+    let current = q_transform.get(sel_entity).ok()?.translation();
+    let parent_transform = parent_transform(sel_entity, q_parents, q_transform).copied().unwrap_or_default();
+    let start = parent_transform.mul_transform(drag.start);
+    (start.translation() != current).then_some((sel_entity, start, current))
+}
+
+/// Draws the bounds of the selected Entity at its pose from the start of the drag, and a line to where it is now.
+pub fn draw_translation_ghost(
+    settings: Res<TransformGizmoSettings>,
+    q_transform: Query<&GlobalTransform>,
+    q_parents: Query<&ChildOf>,
+    q_aabb: Query<&Aabb>,
+    mut gizmos: Gizmos,
+) {
+    let Some((sel_entity, start, current)) = translation_feedback(&settings, &q_transform, &q_parents) else {
+        return;
+    };
+    match q_aabb.get(sel_entity) {
+        Ok(aabb) => {
+            let bounds = Transform::from_translation(aabb.center.into()).with_scale(Vec3::from(aabb.half_extents) * 2.0);
+            gizmos.cuboid(start.mul_transform(bounds), GHOST_COLOR);
+        }
+        // Without bounds, the original axes stand in for the Entity
+        Err(_) => gizmos.axes(start, 0.5),
+    }
+    gizmos.line(start.translation(), current, GHOST_COLOR);
+}
+
+/// Follows the rotation committed to the selected Entity with the angle of the current rotation drag, so the
+/// [`GizmoRotationSector`] and the label show what [`GizmoLimits`](crate::constraints::GizmoLimits) and the
+/// [`GizmoValidator`](crate::validation::GizmoValidator) let through.
+pub fn update_sector_angle(
+    mut settings: ResMut<TransformGizmoSettings>,
+    q_local_transform: Query<&Transform>,
+    q_parents: Query<&ChildOf>,
+    q_transform: Query<&GlobalTransform>,
+) {
+    let Some(sel_entity) = settings.active_entity else {
+        return;
+    };
+    let Some((start, sector)) = settings.drag.and_then(|drag| Some((drag.start, drag.sector?))) else {
+        return;
+    };
+    let Ok(current) = q_local_transform.get(sel_entity) else {
+        return;
+    };
+    // The rotation of the Transform is relative to the parent, measure it around the world space axis of the ring
+    let parent_rotation = parent_transform(sel_entity, &q_parents, &q_transform)
+        .map_or(Quat::IDENTITY, |parent| parent.rotation());
+    let delta = parent_rotation * current.rotation * start.rotation.inverse() * parent_rotation.inverse();
+    let twist = 2.0 * delta.xyz().dot(sector.axis).atan2(delta.w);
+    // The twist repeats every full turn, keep the one closest to the angle so far so turns past ±180° accumulate
+    let angle = sector.angle + (twist - sector.angle + PI).rem_euclid(TAU) - PI;
+    if angle != sector.angle
+        && let Some(sector) = settings.drag.as_mut().and_then(|drag| drag.sector.as_mut())
+    {
+        sector.angle = angle;
+    }
+}
+
+/// Rebuilds the [`GizmoRotationSector`] mesh when the angle of the current rotation drag changes and hides it otherwise.
+pub fn update_rotation_sector(
+    settings: Res<TransformGizmoSettings>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut q_sector: Query<(&Mesh3d, &mut Transform, &mut Visibility), With<GizmoRotationSector>>,
+    mut built: Local<Option<(AssetId<Mesh>, f32, f32)>>,
+) {
+    let sector = settings
+        .drag
+        .filter(|drag| !drag.cancelled && settings.is_dragging())
+        .and_then(|drag| drag.sector);
+    for (mesh, mut transform, mut visibility) in &mut q_sector {
+        let Some(sector) = sector else {
+            visibility.set_if_neq(Visibility::Hidden);
+            continue;
+        };
+        // Past a full turn the sector wraps around, keeping its sign
+        let angle = sector.angle % TAU;
+        // A style change respawns the gizmo with a new mesh, so the mesh, radius and angle identify what was built
+        if *built != Some((mesh.id(), sector.radius, angle)) {
+            if let Some(mesh) = meshes.get_mut(&mesh.0) {
+                *mesh = Mesh::from(Sector {
+                    radius: sector.radius,
+                    angle,
+                    subdivisions: (angle.abs() / TAU * 64.0).ceil() as usize,
+                });
+            }
+            *built = Some((mesh.id(), sector.radius, angle));
+        }
+        *transform = Transform::from_translation(sector.origin).with_rotation(sector.rotation());
+        visibility.set_if_neq(Visibility::Visible);
+    }
+}
+
+/// Places the [`GizmoFeedbackLabel`] next to the gizmo and fills in the signed degrees of the current rotation drag,
+/// or the distance of the current translation drag per axis and in total.
+#[cfg(feature = "ui")]
+pub fn update_feedback_label(
+    settings: Res<TransformGizmoSettings>,
+    q_camera: Query<(&Camera, &GlobalTransform), With<crate::GizmoPickSource>>,
+    q_transform: Query<&GlobalTransform>,
+    q_parents: Query<&ChildOf>,
+    mut q_label: Query<(&mut Text, &mut Node, &mut Visibility), With<GizmoFeedbackLabel>>,
+) {
+    let drag = settings.drag.filter(|drag| !drag.cancelled && settings.is_dragging());
+    let rotation_label = drag.and_then(|drag| drag.sector).map(|sector| {
+        // Halfway along the swept arc, just outside the ring
+        let position = sector.origin
+            + sector.rotation() * Quat::from_rotation_y(sector.angle / 2.0) * Vec3::X * sector.radius * 1.3;
+        (position, format!("{:+.1}°", sector.angle.to_degrees()))
+    });
+    let translation_label = || {
+        let (_, start, current) = translation_feedback(&settings, &q_transform, &q_parents)?;
+        let delta = current - start.translation();
+        Some((
+            start.translation().lerp(current, 0.5),
+            format!(
+                "X {:+.3}  Y {:+.3}  Z {:+.3}  ({:.3})",
+                delta.x,
+                delta.y,
+                delta.z,
+                delta.length()
+            ),
+        ))
+    };
+    let label = rotation_label.or_else(translation_label);
+    // The label is laid out in the window, so the position is moved from the viewport by its origin
+    let viewport_position = label.as_ref().and_then(|(position, _)| {
+        let (camera, camera_transform) = q_camera.single().ok()?;
+        let viewport_origin = camera.logical_viewport_rect().map_or(Vec2::ZERO, |viewport| viewport.min);
+        Some(camera.world_to_viewport(camera_transform, *position).ok()? + viewport_origin)
+    });
+    for (mut text, mut node, mut visibility) in &mut q_label {
+        let (Some((_, content)), Some(viewport_position)) = (&label, viewport_position) else {
+            visibility.set_if_neq(Visibility::Hidden);
+            continue;
+        };
+        if text.0 != *content {
+            text.0.clone_from(content);
+        }
+        node.left = Val::Px(viewport_position.x);
+        node.top = Val::Px(viewport_position.y);
+        visibility.set_if_neq(Visibility::Visible);
+    }
+}
@@ -1,53 +1,224 @@
 use bevy::{
     pbr::NotShadowCaster,
+    picking::Pickable,
     prelude::*,
 };
 
-use crate::*;
+use crate::{
+    feedback::{GizmoFeedbackLabel, GizmoRotationSector},
+    snapping::GizmoSnapMarker,
+    lines::{insert_shape, GizmoLine},
+    picking::{GizmoPickShape, GizmoPrimitive},
+    style::{GizmoThemeColor, GizmoThemeMaterials},
+    validation::GizmoRejectedIndicator,
+    *,
+};
 
 
 
-/// Startup system that builds the procedural mesh and materials of the gizmo.
+/// System that builds the procedural mesh and materials of the gizmo, replacing the gizmo if it already exists.
+///
+/// Only the handles shown in the current [`GizmoMode`] are spawned. A drag on the replaced handles is cancelled.
 pub fn build_gizmo(
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<GizmoMaterial>>,
+    style: Res<TransformGizmoStyle>,
+    theme: Res<GizmoTheme>,
+    mut settings: ResMut<TransformGizmoSettings>,
+    q_roots: Query<Entity, GizmoRoot>,
+    mut q_local_transform: Query<&mut Transform>,
+    mut drag_ended: EventWriter<GizmoDragEnded>,
+) {
+    abandon_active_drag(&mut settings, &mut q_local_transform, &mut drag_ended);
+    // Handles are children of the gizmo root and are despawned with it
+    for entity in &q_roots {
+        commands.entity(entity).despawn();
+    }
+    spawn_gizmo_entities(&mut commands, &mut meshes, &mut materials, &style, &theme, settings.mode());
+}
+
+/// Copy of a handle that draws its parts hidden behind scene geometry, see [`TransformGizmoStyle::show_occluded`].
+#[derive(Component)]
+pub struct GizmoOccludedPart;
+
+/// Gives each new handle a [`GizmoOccludedPart`] child.
+pub fn add_occluded_parts(
+    mut commands: Commands,
+    mut materials: ResMut<Assets<GizmoMaterial>>,
+    style: Res<TransformGizmoStyle>,
+    q_handles: Query<(Entity, &GizmoHandle, &Mesh3d, &MeshMaterial3d<GizmoMaterial>), Added<GizmoHandle>>,
+) {
+    if !style.show_occluded {
+        return;
+    }
+    for (entity, handle, mesh, material) in &q_handles {
+        // The translucent trackball would cover the whole gizmo in stripes
+        if *handle == GizmoHandle::RotateFree {
+            continue;
+        }
+        let Some(occluded_material) = materials.get(&material.0).map(GizmoMaterial::occluded) else {
+            continue;
+        };
+        let occluded_part = commands.spawn((
+            Mesh3d(mesh.0.clone()),
+            MeshMaterial3d(materials.add(occluded_material)),
+            NotShadowCaster,
+            Pickable::IGNORE,
+            TransformGizmoPart,
+            GizmoOccludedPart,
+        )).id();
+        commands.entity(entity).add_children(&[occluded_part]);
+    }
+}
+
+/// Keeps the color of each [`GizmoOccludedPart`] in line with its handle, which changes with hover and theme.
+pub fn sync_occluded_parts(
+    mut materials: ResMut<Assets<GizmoMaterial>>,
+    q_parts: Query<(&ChildOf, &MeshMaterial3d<GizmoMaterial>), With<GizmoOccludedPart>>,
+    q_handles: Query<&MeshMaterial3d<GizmoMaterial>, With<GizmoHandle>>,
+) {
+    for (child_of, material) in &q_parts {
+        let Ok(handle_material) = q_handles.get(child_of.parent()) else {
+            continue;
+        };
+        let Some(color) = materials.get(&handle_material.0).map(|handle_material| handle_material.color) else {
+            continue;
+        };
+        if materials.get(&material.0).is_some_and(|occluded_material| occluded_material.color != color)
+            && let Some(occluded_material) = materials.get_mut(&material.0)
+        {
+            occluded_material.color = color;
+        }
+    }
+}
+
+/// Entities of the gizmo that are not children of another gizmo Entity.
+type GizmoRoot = (
+    Or<(With<TransformGizmo>, With<TransformGizmoPart>, With<GizmoFeedbackLabel>)>,
+    Without<ChildOf>,
+);
+
+fn despawn_gizmo_entities(
+    mut commands: Commands,
+    mut settings: ResMut<TransformGizmoSettings>,
+    q_roots: Query<Entity, GizmoRoot>,
+    mut q_local_transform: Query<&mut Transform>,
+    mut drag_ended: EventWriter<GizmoDragEnded>,
+) {
+    cancel_active_drag(&mut settings, &mut q_local_transform, &mut drag_ended);
+    // maintain_gizmo would spawn the gizmo again for the selected Entity
+    settings.deselect();
+    for entity in &q_roots {
+        commands.entity(entity).despawn();
+    }
+    // The theme keeps handles to the shared materials, they are created again with the gizmo
+    commands.remove_resource::<GizmoThemeMaterials>();
+}
+
+/// Spawns the gizmo, replacing it if it already exists. Use it with [`Commands::queue`].
+pub fn spawn_gizmo(world: &mut World) {
+    if let Err(err) = world.run_system_cached(build_gizmo) {
+        log::warn!("TransformGizmo: Could not spawn the gizmo: {err}");
+    }
+}
+
+/// Deselects the active Entity, cancelling its drag, then despawns the gizmo and frees its meshes and materials. Use it
+/// with [`Commands::queue`].
+///
+/// [`maintain_gizmo`] spawns the gizmo again once an Entity is selected.
+pub fn despawn_gizmo(world: &mut World) {
+    if let Err(err) = world.run_system_cached(despawn_gizmo_entities) {
+        log::warn!("TransformGizmo: Could not despawn the gizmo: {err}");
+    }
+}
+
+/// Spawns the gizmo once an Entity is selected and it does not exist, for example before the first selection or after
+/// a scene reload despawned it. Respawns it when the [`TransformGizmoStyle`] or the [`GizmoMode`] changes.
+pub fn maintain_gizmo(
+    mut commands: Commands,
+    settings: Res<TransformGizmoSettings>,
+    style: Res<TransformGizmoStyle>,
+    q_gizmo: Query<(), With<TransformGizmo>>,
+    mut last_mode: Local<Option<GizmoMode>>,
 ) {
-    let axis_length = 1.3;
-    let arc_radius = 1.;
-    let plane_size = axis_length * 0.25;
-    let plane_offset = plane_size / 2. + axis_length * 0.2;
+    let mode_changed = last_mode.replace(settings.mode()).is_some_and(|mode| mode != settings.mode());
+    let exists = !q_gizmo.is_empty();
+    let outdated = exists && (mode_changed || (style.is_changed() && !style.is_added()));
+    if outdated || (!exists && settings.is_active()) {
+        commands.queue(spawn_gizmo);
+    }
+}
+
+fn spawn_gizmo_entities(
+    commands: &mut Commands,
+    meshes: &mut Assets<Mesh>,
+    materials: &mut Assets<GizmoMaterial>,
+    style: &TransformGizmoStyle,
+    theme: &GizmoTheme,
+    mode: GizmoMode,
+) {
+    let axis_length = style.axis_length;
+    let arc_radius = style.arc_radius;
+    let thickness = style.line_thickness;
+    let plane_size = style.plane_size;
+    let plane_offset = plane_size / 2. + style.plane_offset;
+    let scale_offset = style.scale_offset;
     // Define gizmo meshes
     let arrow_tail_mesh = meshes.add(Capsule3d {
-        radius: 0.04,
+        radius: thickness,
         half_length: axis_length * 0.5f32,
     });
     let arrow_tail_mesh_short = meshes.add(Capsule3d {
-        radius: 0.04,
+        radius: thickness,
         half_length: axis_length * 0.333f32,
     });
     let cone_mesh = meshes.add(cone::Cone {
-        height: 0.25,
-        radius: 0.10,
+        height: style.cone_height,
+        radius: style.cone_radius,
         ..Default::default()
     });
     let plane_mesh = meshes.add(Plane3d::default().mesh().size(plane_size, plane_size));
-    let sphere_mesh = meshes.add(Sphere { radius: 0.2 });
+    let sphere_mesh = meshes.add(Sphere { radius: style.center_radius });
+    let scale_mesh = meshes.add(Cuboid::from_size(Vec3::splat(style.scale_handle_size)));
+    let scale_uniform_mesh = meshes.add(Cuboid::from_size(Vec3::splat(style.scale_uniform_size)));
+    let snap_marker_mesh = meshes.add(Sphere { radius: thickness * 1.5 });
     let rotation_mesh = meshes.add(Mesh::from(truncated_torus::TruncatedTorus {
         radius: arc_radius,
-        ring_radius: 0.04,
+        ring_radius: thickness,
+        ..Default::default()
+    }));
+    let view_rotation_mesh = meshes.add(Mesh::from(truncated_torus::TruncatedTorus {
+        radius: style.view_ring_radius,
+        ring_radius: thickness,
+        angle: std::f32::consts::TAU,
+        ..Default::default()
+    }));
+    // Rebuilt every frame of a rotation drag by update_rotation_sector
+    let sector_mesh = meshes.add(Mesh::from(sector::Sector::default()));
+    let trackball_mesh = meshes.add(Sphere { radius: style.trackball_radius });
+    let rejected_mesh = meshes.add(Mesh::from(truncated_torus::TruncatedTorus {
+        radius: arc_radius * 0.4,
+        ring_radius: thickness * 0.75,
+        angle: std::f32::consts::TAU,
         ..Default::default()
     }));
 
     // Define gizmo materials
-    let (s, l) = (0.8, 0.6);
-    let gizmo_matl_x = materials.add(GizmoMaterial::from(Color::hsl(0.0, s, l)));
-    let gizmo_matl_y = materials.add(GizmoMaterial::from(Color::hsl(120.0, s, l)));
-    let gizmo_matl_z = materials.add(GizmoMaterial::from(Color::hsl(240.0, s, l)));
-    let gizmo_matl_x_sel = materials.add(GizmoMaterial::from(Color::hsl(0.0, s, l)));
-    let gizmo_matl_y_sel = materials.add(GizmoMaterial::from(Color::hsl(120.0, s, l)));
-    let gizmo_matl_z_sel = materials.add(GizmoMaterial::from(Color::hsl(240.0, s, l)));
-    let gizmo_matl_v_sel = materials.add(GizmoMaterial::from(Color::hsl(0., 0.0, l)));
+    let theme_materials = GizmoThemeMaterials::new(materials, theme, style.shading);
+    let gizmo_matl_x = theme_materials.base(GizmoThemeColor::Axis(GizmoAxis::X));
+    let gizmo_matl_y = theme_materials.base(GizmoThemeColor::Axis(GizmoAxis::Y));
+    let gizmo_matl_z = theme_materials.base(GizmoThemeColor::Axis(GizmoAxis::Z));
+    let gizmo_matl_plane_x = theme_materials.base(GizmoThemeColor::Plane(GizmoAxis::X));
+    let gizmo_matl_plane_y = theme_materials.base(GizmoThemeColor::Plane(GizmoAxis::Y));
+    let gizmo_matl_plane_z = theme_materials.base(GizmoThemeColor::Plane(GizmoAxis::Z));
+    let gizmo_matl_center = theme_materials.base(GizmoThemeColor::Center);
+    commands.insert_resource(theme_materials);
+    let l = 0.6;
+    let gizmo_matl_trackball = materials.add(GizmoMaterial::new(Color::hsla(0., 0.0, l, 0.15), style.shading));
+    let gizmo_matl_sector = materials.add(GizmoMaterial::new(Color::hsla(50., 0.9, l, 0.3), style.shading));
+    let gizmo_matl_rejected = materials.add(GizmoMaterial::new(Color::srgb(1.0, 0.1, 0.1), style.shading));
+    let gizmo_matl_snap = materials.add(GizmoMaterial::new(Color::srgb(1.0, 0.85, 0.1), style.shading));
 
     // Build the gizmo using the variables above.
     let parent = commands.spawn((
@@ -55,173 +226,402 @@ pub fn build_gizmo(
         Visibility::Hidden,
         TransformGizmo,
         )).id();
-    commands.entity(parent).observe(transform_drag_end);
+    commands.entity(parent).observe(transform_drag_start).observe(transform_drag_end);
 
 
     // Translation Axes
-    let translation_x_axis = commands.spawn((
-        Mesh3d(arrow_tail_mesh.clone()),
-        MeshMaterial3d(gizmo_matl_x.clone()),
-        Transform::from_matrix(Mat4::from_rotation_translation(
-            Quat::from_rotation_z(std::f32::consts::PI / 2.0),
-            Vec3::new(axis_length / 2.0, 0.0, 0.0),
-        )),
-        NotShadowCaster,
-        TransformGizmoPart,
-    )).id();
-    commands.entity(parent).add_children(&[translation_x_axis]);
-    commands.entity(translation_x_axis).observe(transform_axis).observe(transform_drag_end);
-
-    let translation_y_axis = commands.spawn((
-        Mesh3d(arrow_tail_mesh_short.clone()),
-        MeshMaterial3d(gizmo_matl_y.clone()),
-        Transform::from_matrix(Mat4::from_rotation_translation(
-            Quat::from_rotation_y(std::f32::consts::PI / 2.0),
-            Vec3::new(0.0, axis_length / 3.0, 0.0),
-        )),
-        NotShadowCaster,
-        TransformGizmoPart,
-    )).id();
-    commands.entity(parent).add_children(&[translation_y_axis]);
-    commands.entity(translation_y_axis).observe(transform_axis).observe(transform_drag_end);
-
-    let translation_z_axis = commands.spawn((
-        Mesh3d(arrow_tail_mesh.clone()),
-        MeshMaterial3d(gizmo_matl_z.clone()),
-        Transform::from_matrix(Mat4::from_rotation_translation(
-            Quat::from_rotation_x(std::f32::consts::PI / 2.0),
-            Vec3::new(0.0, 0.0, axis_length / 2.0),
-        )),
-        NotShadowCaster,
-        TransformGizmoPart,
-    )).id();
-    commands.entity(parent).add_children(&[translation_z_axis]);
-    commands.entity(translation_z_axis).observe(transform_axis).observe(transform_drag_end);
+    if mode.shows(GizmoHandle::TranslateAxis(GizmoAxis::X)) {
+        let translation_x_axis = commands.spawn((
+            Transform::from_matrix(Mat4::from_rotation_translation(
+                Quat::from_rotation_z(std::f32::consts::PI / 2.0),
+                Vec3::new(axis_length / 2.0, 0.0, 0.0),
+            )),
+            NotShadowCaster,
+            TransformGizmoPart,
+            GizmoHandle::TranslateAxis(GizmoAxis::X),
+        )).id();
+        insert_shape(
+            &mut commands.entity(translation_x_axis),
+            style.render_mode,
+            GizmoLine::vertical(axis_length * 0.5),
+            arrow_tail_mesh.clone(),
+            gizmo_matl_x.clone(),
+            GizmoPickShape::new(GizmoPrimitive::Cylinder { radius: thickness, half_length: axis_length * 0.5 }, 1),
+        );
+        commands.entity(parent).add_children(&[translation_x_axis]);
+        commands.entity(translation_x_axis).observe(transform_axis).observe(transform_drag_end).observe(mark_active_handle);
+    }
+
+    if mode.shows(GizmoHandle::TranslateAxis(GizmoAxis::Y)) {
+        let translation_y_axis = commands.spawn((
+            Transform::from_matrix(Mat4::from_rotation_translation(
+                Quat::from_rotation_y(std::f32::consts::PI / 2.0),
+                Vec3::new(0.0, axis_length / 3.0, 0.0),
+            )),
+            NotShadowCaster,
+            TransformGizmoPart,
+            GizmoHandle::TranslateAxis(GizmoAxis::Y),
+        )).id();
+        insert_shape(
+            &mut commands.entity(translation_y_axis),
+            style.render_mode,
+            GizmoLine::vertical(axis_length * 0.333),
+            arrow_tail_mesh_short.clone(),
+            gizmo_matl_y.clone(),
+            GizmoPickShape::new(GizmoPrimitive::Cylinder { radius: thickness, half_length: axis_length * 0.333 }, 1),
+        );
+        commands.entity(parent).add_children(&[translation_y_axis]);
+        commands.entity(translation_y_axis).observe(transform_axis).observe(transform_drag_end).observe(mark_active_handle);
+    }
+
+    if mode.shows(GizmoHandle::TranslateAxis(GizmoAxis::Z)) {
+        let translation_z_axis = commands.spawn((
+            Transform::from_matrix(Mat4::from_rotation_translation(
+                Quat::from_rotation_x(std::f32::consts::PI / 2.0),
+                Vec3::new(0.0, 0.0, axis_length / 2.0),
+            )),
+            NotShadowCaster,
+            TransformGizmoPart,
+            GizmoHandle::TranslateAxis(GizmoAxis::Z),
+        )).id();
+        insert_shape(
+            &mut commands.entity(translation_z_axis),
+            style.render_mode,
+            GizmoLine::vertical(axis_length * 0.5),
+            arrow_tail_mesh.clone(),
+            gizmo_matl_z.clone(),
+            GizmoPickShape::new(GizmoPrimitive::Cylinder { radius: thickness, half_length: axis_length * 0.5 }, 1),
+        );
+        commands.entity(parent).add_children(&[translation_z_axis]);
+        commands.entity(translation_z_axis).observe(transform_axis).observe(transform_drag_end).observe(mark_active_handle);
+    }
 
     // Translation Handles
-    let translation_x_handle = commands.spawn((
-        Mesh3d(cone_mesh.clone()),
-        MeshMaterial3d(gizmo_matl_x_sel.clone()),
-        Transform::from_matrix(Mat4::from_rotation_translation(
-            Quat::from_rotation_z(std::f32::consts::PI / -2.0),
-            Vec3::new(axis_length, 0.0, 0.0),
-        )),
-        NotShadowCaster,
-        TransformGizmoPart,
-    )).id();
-    commands.entity(parent).add_children(&[translation_x_handle]);
-    commands.entity(translation_x_handle).observe(transform_axis).observe(transform_drag_end);
-
-    let translation_x_plane = commands.spawn((
-        Mesh3d(plane_mesh.clone()),
-        MeshMaterial3d(gizmo_matl_x_sel.clone()),
-        Transform::from_matrix(Mat4::from_rotation_translation(
-            Quat::from_rotation_z(std::f32::consts::PI / -2.0),
-            Vec3::new(0., plane_offset, plane_offset),
-        )),
-        NotShadowCaster,
-        TransformGizmoPart,
-    )).id();
-    commands.entity(parent).add_children(&[translation_x_plane]);
-    commands.entity(translation_x_plane).observe(transform_plane).observe(transform_drag_end);
+    if mode.shows(GizmoHandle::TranslateAxis(GizmoAxis::X)) {
+        let translation_x_handle = commands.spawn((
+            Mesh3d(cone_mesh.clone()),
+            MeshMaterial3d(gizmo_matl_x.clone()),
+            Transform::from_matrix(Mat4::from_rotation_translation(
+                Quat::from_rotation_z(std::f32::consts::PI / -2.0),
+                Vec3::new(axis_length, 0.0, 0.0),
+            )),
+            NotShadowCaster,
+            TransformGizmoPart,
+            GizmoHandle::TranslateAxis(GizmoAxis::X),
+            GizmoPickShape::new(GizmoPrimitive::Cone { radius: style.cone_radius, height: style.cone_height }, 3),
+        )).id();
+        commands.entity(parent).add_children(&[translation_x_handle]);
+        commands.entity(translation_x_handle).observe(transform_axis).observe(transform_drag_end).observe(mark_active_handle);
+    }
 
+    if mode.shows(GizmoHandle::TranslatePlane(GizmoAxis::X)) {
+        let translation_x_plane = commands.spawn((
+            Mesh3d(plane_mesh.clone()),
+            MeshMaterial3d(gizmo_matl_plane_x.clone()),
+            Transform::from_matrix(Mat4::from_rotation_translation(
+                Quat::from_rotation_z(std::f32::consts::PI / -2.0),
+                Vec3::new(0., plane_offset, plane_offset),
+            )),
+            NotShadowCaster,
+            TransformGizmoPart,
+            GizmoHandle::TranslatePlane(GizmoAxis::X),
+            GizmoPickShape::new(GizmoPrimitive::Cuboid { half_size: Vec3::new(plane_size * 0.5, 0.0, plane_size * 0.5) }, 2),
+        )).id();
+        commands.entity(parent).add_children(&[translation_x_plane]);
+        commands.entity(translation_x_plane).observe(transform_plane).observe(transform_drag_end).observe(mark_active_handle);
+    }
 
-    let translation_y_handle = commands.spawn((
-        Mesh3d(cone_mesh.clone()),
-        MeshMaterial3d(gizmo_matl_y_sel.clone()),
-        Transform::from_translation(Vec3::new(0.0, axis_length * 0.667, 0.0)),
-        NotShadowCaster,
-        TransformGizmoPart,
-    )).id();
-    commands.entity(parent).add_children(&[translation_y_handle]);
-    commands.entity(translation_y_handle).observe(transform_axis).observe(transform_drag_end);
-
-
-    let translation_y_plane = commands.spawn((
-        Mesh3d(plane_mesh.clone()),
-        MeshMaterial3d(gizmo_matl_y_sel.clone()),
-         Transform::from_translation(Vec3::new(
-            plane_offset,
-            0.0,
-            plane_offset,
-        )),
-        NotShadowCaster,
-        TransformGizmoPart,
-    )).id();
-    commands.entity(parent).add_children(&[translation_y_plane]);
-    commands.entity(translation_y_plane).observe(transform_plane).observe(transform_drag_end);
-
-    let translation_z_handle = commands.spawn((
-        Mesh3d(cone_mesh.clone()),
-        MeshMaterial3d(gizmo_matl_z_sel.clone()),
-        Transform::from_matrix(Mat4::from_rotation_translation(
-            Quat::from_rotation_x(std::f32::consts::PI / 2.0),
-            Vec3::new(0.0, 0.0, axis_length),
-        )),
+
+    if mode.shows(GizmoHandle::TranslateAxis(GizmoAxis::Y)) {
+        let translation_y_handle = commands.spawn((
+            Mesh3d(cone_mesh.clone()),
+            MeshMaterial3d(gizmo_matl_y.clone()),
+            Transform::from_translation(Vec3::new(0.0, axis_length * 0.667, 0.0)),
+            NotShadowCaster,
+            TransformGizmoPart,
+            GizmoHandle::TranslateAxis(GizmoAxis::Y),
+            GizmoPickShape::new(GizmoPrimitive::Cone { radius: style.cone_radius, height: style.cone_height }, 3),
+        )).id();
+        commands.entity(parent).add_children(&[translation_y_handle]);
+        commands.entity(translation_y_handle).observe(transform_axis).observe(transform_drag_end).observe(mark_active_handle);
+    }
+
+
+    if mode.shows(GizmoHandle::TranslatePlane(GizmoAxis::Y)) {
+        let translation_y_plane = commands.spawn((
+            Mesh3d(plane_mesh.clone()),
+            MeshMaterial3d(gizmo_matl_plane_y.clone()),
+             Transform::from_translation(Vec3::new(
+                plane_offset,
+                0.0,
+                plane_offset,
+            )),
+            NotShadowCaster,
+            TransformGizmoPart,
+            GizmoHandle::TranslatePlane(GizmoAxis::Y),
+            GizmoPickShape::new(GizmoPrimitive::Cuboid { half_size: Vec3::new(plane_size * 0.5, 0.0, plane_size * 0.5) }, 2),
+        )).id();
+        commands.entity(parent).add_children(&[translation_y_plane]);
+        commands.entity(translation_y_plane).observe(transform_plane).observe(transform_drag_end).observe(mark_active_handle);
+    }
+
+    if mode.shows(GizmoHandle::TranslateAxis(GizmoAxis::Z)) {
+        let translation_z_handle = commands.spawn((
+            Mesh3d(cone_mesh.clone()),
+            MeshMaterial3d(gizmo_matl_z.clone()),
+            Transform::from_matrix(Mat4::from_rotation_translation(
+                Quat::from_rotation_x(std::f32::consts::PI / 2.0),
+                Vec3::new(0.0, 0.0, axis_length),
+            )),
+            NotShadowCaster,
+            TransformGizmoPart,
+            GizmoHandle::TranslateAxis(GizmoAxis::Z),
+            GizmoPickShape::new(GizmoPrimitive::Cone { radius: style.cone_radius, height: style.cone_height }, 3),
+        )).id();
+        commands.entity(parent).add_children(&[translation_z_handle]);
+        commands.entity(translation_z_handle).observe(transform_axis).observe(transform_drag_end).observe(mark_active_handle);
+    }
+
+    if mode.shows(GizmoHandle::TranslatePlane(GizmoAxis::Z)) {
+        let translation_z_plane = commands.spawn((
+            Mesh3d(plane_mesh.clone()),
+            MeshMaterial3d(gizmo_matl_plane_z.clone()),
+            Transform::from_matrix(Mat4::from_rotation_translation(
+                Quat::from_rotation_x(std::f32::consts::PI / 2.0),
+                Vec3::new(plane_offset, plane_offset, 0.0),
+            )),
+            NotShadowCaster,
+            TransformGizmoPart,
+            GizmoHandle::TranslatePlane(GizmoAxis::Z),
+            GizmoPickShape::new(GizmoPrimitive::Cuboid { half_size: Vec3::new(plane_size * 0.5, 0.0, plane_size * 0.5) }, 2),
+        )).id();
+        commands.entity(parent).add_children(&[translation_z_plane]);
+        commands.entity(translation_z_plane).observe(transform_plane).observe(transform_drag_end).observe(mark_active_handle);
+    }
+
+    if mode.shows(GizmoHandle::TranslateView) {
+        let handle = commands.spawn((
+            Mesh3d(sphere_mesh.clone()),
+            MeshMaterial3d(gizmo_matl_center.clone()),
+            NotShadowCaster,
+            TransformGizmoPart,
+            GizmoHandle::TranslateView,
+            GizmoPickShape::new(GizmoPrimitive::Sphere { radius: style.center_radius }, 3),
+        )).id();
+        commands.entity(parent).add_children(&[handle]);
+        commands.entity(handle).observe(transform_camera_plane).observe(transform_drag_end).observe(mark_active_handle);
+    }
+
+    // Shown while the GizmoValidator rejects the proposed transform
+    let rejected_indicator = commands.spawn((
+        Mesh3d(rejected_mesh.clone()),
+        MeshMaterial3d(gizmo_matl_rejected.clone()),
+        Visibility::Hidden,
         NotShadowCaster,
+        Pickable::IGNORE,
         TransformGizmoPart,
+        GizmoRejectedIndicator,
     )).id();
-    commands.entity(parent).add_children(&[translation_z_handle]);
-    commands.entity(translation_z_handle).observe(transform_axis).observe(transform_drag_end);
-
-    let translation_z_plane = commands.spawn((
-        Mesh3d(plane_mesh.clone()),
-        MeshMaterial3d( gizmo_matl_z_sel.clone()),
-        Transform::from_matrix(Mat4::from_rotation_translation(
-            Quat::from_rotation_x(std::f32::consts::PI / 2.0),
-            Vec3::new(plane_offset, plane_offset, 0.0),
-        )),
+    commands.entity(parent).add_children(&[rejected_indicator]);
+
+    // Marks the vertex the selected Entity snaps onto, it lives in world space next to the gizmo
+    commands.spawn((
+        Mesh3d(snap_marker_mesh.clone()),
+        MeshMaterial3d(gizmo_matl_snap.clone()),
+        Visibility::Hidden,
         NotShadowCaster,
+        Pickable::IGNORE,
         TransformGizmoPart,
-    )).id();
-    commands.entity(parent).add_children(&[translation_z_plane]);
-    commands.entity(translation_z_plane).observe(transform_plane).observe(transform_drag_end);
+        GizmoSnapMarker,
+    ));
 
-    let handle = commands.spawn((
-        Mesh3d(sphere_mesh.clone()),
-        MeshMaterial3d(gizmo_matl_v_sel.clone()),
+    // Shows the angle of the current rotation drag, it lives in world space so it stays put while the Entity turns
+    commands.spawn((
+        Mesh3d(sector_mesh.clone()),
+        MeshMaterial3d(gizmo_matl_sector.clone()),
+        Visibility::Hidden,
         NotShadowCaster,
+        Pickable::IGNORE,
         TransformGizmoPart,
-    )).id();
-    commands.entity(parent).add_children(&[handle]);
-    commands.entity(handle).observe(transform_camera_plane).observe(transform_drag_end);
+        GizmoRotationSector,
+    ));
 
+    // Readout of the current manipulation, placed next to the gizmo in screen space
+    #[cfg(feature = "ui")]
+    commands.spawn((
+        Text::new(""),
+        TextFont {
+            font_size: 16.0,
+            ..default()
+        },
+        TextColor(Color::WHITE),
+        Node {
+            position_type: PositionType::Absolute,
+            ..default()
+        },
+        Visibility::Hidden,
+        Pickable::IGNORE,
+        GizmoFeedbackLabel,
+    ));
 
     // Rotation Arcs
-    // let rotation_x_arc = commands.spawn((
-    //     Mesh3d(rotation_mesh.clone()),
-    //     MeshMaterial3d(gizmo_matl_x.clone()),
-    //     Transform::from_rotation(Quat::from_axis_angle(
-    //         Vec3::Z,
-    //         f32::to_radians(90.0),
-    //     )),
-    //     NotShadowCaster,
-    //     TransformGizmoPart,
-    // )).id();
-    // commands.entity(parent).add_children(&[rotation_x_arc]);
-    // commands.entity(rotation_x_arc).observe(transform_rotation);
-
-    let rotation_y_arc = commands.spawn((
-        Mesh3d(rotation_mesh.clone()),
-        MeshMaterial3d(gizmo_matl_y.clone()),
-        NotShadowCaster,
-        TransformGizmoPart,
-    )).id();
-    commands.entity(parent).add_children(&[rotation_y_arc]);
-    commands.entity(rotation_y_arc).observe(transform_rotation).observe(transform_drag_end);
-
-    // let rotation_z_arc = commands.spawn((
-    //     Mesh3d(rotation_mesh.clone()),
-    //     MeshMaterial3d(gizmo_matl_z.clone()),
-    //     Transform::from_rotation(
-    //         Quat::from_axis_angle(Vec3::Z, f32::to_radians(90.0))
-    //             * Quat::from_axis_angle(Vec3::X, f32::to_radians(90.0)),
-    //     ),
-    //     NotShadowCaster,
-    //     TransformGizmoPart,
-    // )).id();
-    // commands.entity(parent).add_children(&[rotation_z_arc]);
-    // commands.entity(rotation_z_arc).observe(transform_rotation);
+    if mode.shows(GizmoHandle::RotateAxis(GizmoAxis::X)) {
+        let rotation_x_arc = commands.spawn((
+            Transform::from_rotation(Quat::from_axis_angle(
+                Vec3::Z,
+                f32::to_radians(90.0),
+            )),
+            NotShadowCaster,
+            TransformGizmoPart,
+            GizmoHandle::RotateAxis(GizmoAxis::X),
+        )).id();
+        insert_shape(
+            &mut commands.entity(rotation_x_arc),
+            style.render_mode,
+            GizmoLine::Arc { radius: arc_radius, angle: std::f32::consts::FRAC_PI_2 },
+            rotation_mesh.clone(),
+            gizmo_matl_x.clone(),
+            GizmoPickShape::new(GizmoPrimitive::Torus { radius: arc_radius, ring_radius: thickness, angle: std::f32::consts::FRAC_PI_2 }, 1),
+        );
+        commands.entity(parent).add_children(&[rotation_x_arc]);
+        commands.entity(rotation_x_arc).observe(transform_rotation).observe(transform_drag_end).observe(mark_active_handle);
+    }
+
+    if mode.shows(GizmoHandle::RotateAxis(GizmoAxis::Y)) {
+        let rotation_y_arc = commands.spawn((
+            NotShadowCaster,
+            TransformGizmoPart,
+            GizmoHandle::RotateAxis(GizmoAxis::Y),
+        )).id();
+        insert_shape(
+            &mut commands.entity(rotation_y_arc),
+            style.render_mode,
+            GizmoLine::Arc { radius: arc_radius, angle: std::f32::consts::FRAC_PI_2 },
+            rotation_mesh.clone(),
+            gizmo_matl_y.clone(),
+            GizmoPickShape::new(GizmoPrimitive::Torus { radius: arc_radius, ring_radius: thickness, angle: std::f32::consts::FRAC_PI_2 }, 1),
+        );
+        commands.entity(parent).add_children(&[rotation_y_arc]);
+        commands.entity(rotation_y_arc).observe(transform_rotation).observe(transform_drag_end).observe(mark_active_handle);
+    }
+
+    if mode.shows(GizmoHandle::RotateAxis(GizmoAxis::Z)) {
+        let rotation_z_arc = commands.spawn((
+            Transform::from_rotation(
+                Quat::from_axis_angle(Vec3::Z, f32::to_radians(90.0))
+                    * Quat::from_axis_angle(Vec3::X, f32::to_radians(90.0)),
+            ),
+            NotShadowCaster,
+            TransformGizmoPart,
+            GizmoHandle::RotateAxis(GizmoAxis::Z),
+        )).id();
+        insert_shape(
+            &mut commands.entity(rotation_z_arc),
+            style.render_mode,
+            GizmoLine::Arc { radius: arc_radius, angle: std::f32::consts::FRAC_PI_2 },
+            rotation_mesh.clone(),
+            gizmo_matl_z.clone(),
+            GizmoPickShape::new(GizmoPrimitive::Torus { radius: arc_radius, ring_radius: thickness, angle: std::f32::consts::FRAC_PI_2 }, 1),
+        );
+        commands.entity(parent).add_children(&[rotation_z_arc]);
+        commands.entity(rotation_z_arc).observe(transform_rotation).observe(transform_drag_end).observe(mark_active_handle);
+    }
+
+    // Outer ring, turned toward the camera every frame by update_view_ring
+    if mode.shows(GizmoHandle::RotateView) {
+        let rotation_view_arc = commands.spawn((
+            Transform::default(),
+            NotShadowCaster,
+            TransformGizmoPart,
+            GizmoHandle::RotateView,
+        )).id();
+        insert_shape(
+            &mut commands.entity(rotation_view_arc),
+            style.render_mode,
+            GizmoLine::Arc { radius: style.view_ring_radius, angle: std::f32::consts::TAU },
+            view_rotation_mesh.clone(),
+            gizmo_matl_center.clone(),
+            GizmoPickShape::new(GizmoPrimitive::Torus { radius: style.view_ring_radius, ring_radius: thickness, angle: std::f32::consts::TAU }, 1),
+        );
+        commands.entity(parent).add_children(&[rotation_view_arc]);
+        commands.entity(rotation_view_arc).observe(transform_rotation).observe(transform_drag_end).observe(mark_active_handle);
+    }
+
+    // Trackball, only shown in GizmoMode::Rotate so it does not cover the translation handles
+    if mode.shows(GizmoHandle::RotateFree) {
+        let rotation_trackball = commands.spawn((
+            Mesh3d(trackball_mesh.clone()),
+            MeshMaterial3d(gizmo_matl_trackball.clone()),
+            NotShadowCaster,
+            TransformGizmoPart,
+            GizmoHandle::RotateFree,
+            GizmoPickShape::new(GizmoPrimitive::Sphere { radius: style.trackball_radius }, 0),
+        )).id();
+        commands.entity(parent).add_children(&[rotation_trackball]);
+        commands.entity(rotation_trackball).observe(transform_free_rotation).observe(transform_drag_end).observe(mark_active_handle);
+    }
+
+    // Scale Handles
+    if mode.shows(GizmoHandle::ScaleAxis(GizmoAxis::X)) {
+        let scale_x_handle = commands.spawn((
+            Mesh3d(scale_mesh.clone()),
+            MeshMaterial3d(gizmo_matl_x.clone()),
+            Transform::from_matrix(Mat4::from_rotation_translation(
+                Quat::from_rotation_z(std::f32::consts::PI / -2.0),
+                Vec3::new(scale_offset, 0.0, 0.0),
+            )),
+            NotShadowCaster,
+            TransformGizmoPart,
+            GizmoHandle::ScaleAxis(GizmoAxis::X),
+            GizmoPickShape::new(GizmoPrimitive::Cuboid { half_size: Vec3::splat(style.scale_handle_size * 0.5) }, 3),
+        )).id();
+        commands.entity(parent).add_children(&[scale_x_handle]);
+        commands.entity(scale_x_handle).observe(transform_scale).observe(transform_drag_end).observe(mark_active_handle);
+    }
+
+    if mode.shows(GizmoHandle::ScaleAxis(GizmoAxis::Y)) {
+        let scale_y_handle = commands.spawn((
+            Mesh3d(scale_mesh.clone()),
+            MeshMaterial3d(gizmo_matl_y.clone()),
+            Transform::from_translation(Vec3::new(0.0, scale_offset, 0.0)),
+            NotShadowCaster,
+            TransformGizmoPart,
+            GizmoHandle::ScaleAxis(GizmoAxis::Y),
+            GizmoPickShape::new(GizmoPrimitive::Cuboid { half_size: Vec3::splat(style.scale_handle_size * 0.5) }, 3),
+        )).id();
+        commands.entity(parent).add_children(&[scale_y_handle]);
+        commands.entity(scale_y_handle).observe(transform_scale).observe(transform_drag_end).observe(mark_active_handle);
+    }
+
+    if mode.shows(GizmoHandle::ScaleAxis(GizmoAxis::Z)) {
+        let scale_z_handle = commands.spawn((
+            Mesh3d(scale_mesh.clone()),
+            MeshMaterial3d(gizmo_matl_z.clone()),
+            Transform::from_matrix(Mat4::from_rotation_translation(
+                Quat::from_rotation_x(std::f32::consts::PI / 2.0),
+                Vec3::new(0.0, 0.0, scale_offset),
+            )),
+            NotShadowCaster,
+            TransformGizmoPart,
+            GizmoHandle::ScaleAxis(GizmoAxis::Z),
+            GizmoPickShape::new(GizmoPrimitive::Cuboid { half_size: Vec3::splat(style.scale_handle_size * 0.5) }, 3),
+        )).id();
+        commands.entity(parent).add_children(&[scale_z_handle]);
+        commands.entity(scale_z_handle).observe(transform_scale).observe(transform_drag_end).observe(mark_active_handle);
+    }
+
+    if mode.shows(GizmoHandle::ScaleUniform) {
+        let scale_uniform_handle = commands.spawn((
+            Mesh3d(scale_uniform_mesh.clone()),
+            MeshMaterial3d(gizmo_matl_center.clone()),
+            NotShadowCaster,
+            TransformGizmoPart,
+            GizmoHandle::ScaleUniform,
+            GizmoPickShape::new(GizmoPrimitive::Cuboid { half_size: Vec3::splat(style.scale_uniform_size * 0.5) }, 3),
+        )).id();
+        commands.entity(parent).add_children(&[scale_uniform_handle]);
+        commands.entity(scale_uniform_handle).observe(transform_scale).observe(transform_drag_end).observe(mark_active_handle);
+    }
 
     // commands.spawn((
     //     Camera3d {
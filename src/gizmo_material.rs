@@ -2,23 +2,90 @@ use bevy::{
     asset::weak_handle, pbr::{MaterialPipeline, MaterialPipelineKey}, prelude::*, reflect::TypePath, render::{
         mesh::MeshVertexBufferLayoutRef,
         render_resource::{
-            AsBindGroup, RenderPipelineDescriptor, ShaderRef, SpecializedMeshPipelineError,
+            AsBindGroup, CompareFunction, RenderPipelineDescriptor, ShaderRef, SpecializedMeshPipelineError,
         },
     }
 };
 
 pub const GIZMO_SHADER_HANDLE: Handle<Shader> = weak_handle!("1347c9b7-c46a-48e7-b7b8-023a354b7cbc");
 
+pub use shading::GizmoShading;
+
+mod shading {
+    // The ShaderType derive of encase 0.10 emits `check` functions that newer compilers report as never used
+    #![allow(dead_code)]
+
+    use bevy::render::render_resource::ShaderType;
+
+    /// View dependent shading of the gizmo handles.
+    #[derive(ShaderType, Debug, Clone, Copy, PartialEq)]
+    pub struct GizmoShading {
+        /// Brightness added where the surface turns away from the camera, 0 draws flat colors.
+        pub rim_strength: f32,
+        /// Falloff of the rim, higher values make it narrower.
+        pub rim_power: f32,
+        /// Length in pixels of the stripes drawn where a handle is hidden behind scene geometry.
+        pub dash_length: f32,
+        /// Opacity of those stripes relative to the handle color.
+        pub occluded_alpha: f32,
+    }
+
+    impl Default for GizmoShading {
+        fn default() -> Self {
+            GizmoShading {
+                rim_strength: 0.35,
+                rim_power: 2.0,
+                dash_length: 4.0,
+                occluded_alpha: 0.35,
+            }
+        }
+    }
+}
 
 #[derive(Asset, TypePath, AsBindGroup, Debug, Clone)]
+#[bind_group_data(GizmoMaterialKey)]
 pub struct GizmoMaterial {
     #[uniform(0)]
     pub color: LinearRgba,
+    #[uniform(1)]
+    pub shading: GizmoShading,
+    /// Draws only the parts hidden behind scene geometry, as stripes.
+    pub occluded: bool,
+}
+
+impl GizmoMaterial {
+    pub fn new(color: Color, shading: GizmoShading) -> Self {
+        GizmoMaterial {
+            color: color.into(),
+            shading,
+            occluded: false,
+        }
+    }
+    /// Returns a copy that draws the parts of a handle hidden behind scene geometry.
+    pub fn occluded(&self) -> Self {
+        GizmoMaterial {
+            occluded: true,
+            ..self.clone()
+        }
+    }
 }
 
 impl From<Color> for GizmoMaterial {
     fn from(color: Color) -> Self {
-        GizmoMaterial { color: color.into() }
+        GizmoMaterial::new(color, GizmoShading::default())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct GizmoMaterialKey {
+    occluded: bool,
+}
+
+impl From<&GizmoMaterial> for GizmoMaterialKey {
+    fn from(material: &GizmoMaterial) -> Self {
+        GizmoMaterialKey {
+            occluded: material.occluded,
+        }
     }
 }
 
@@ -32,16 +99,30 @@ impl Material for GizmoMaterial {
     }
 
     fn alpha_mode(&self) -> AlphaMode {
-        AlphaMode::Opaque
+        if self.occluded || self.color.alpha < 1.0 {
+            AlphaMode::Blend
+        } else {
+            AlphaMode::Opaque
+        }
     }
 
     fn specialize(
         _pipeline: &MaterialPipeline<Self>,
         descriptor: &mut RenderPipelineDescriptor,
         _layout: &MeshVertexBufferLayoutRef,
-        _key: MaterialPipelineKey<Self>,
+        key: MaterialPipelineKey<Self>,
     ) -> Result<(), SpecializedMeshPipelineError> {
         descriptor.primitive.cull_mode = None;
+        if key.bind_group_data.occluded {
+            // Depth is reversed, so Less only passes behind what was already drawn
+            if let Some(depth_stencil) = descriptor.depth_stencil.as_mut() {
+                depth_stencil.depth_compare = CompareFunction::Less;
+                depth_stencil.depth_write_enabled = false;
+            }
+            if let Some(fragment) = descriptor.fragment.as_mut() {
+                fragment.shader_defs.push("GIZMO_OCCLUDED".into());
+            }
+        }
         Ok(())
     }
 }
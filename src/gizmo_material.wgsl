@@ -1,32 +1,60 @@
-#import bevy_pbr::mesh_functions::{get_world_from_local, mesh_position_local_to_clip}
+#import bevy_pbr::{
+    mesh_functions::{get_world_from_local, mesh_position_local_to_clip, mesh_position_local_to_world, mesh_normal_local_to_world},
+    mesh_view_bindings::view,
+}
 
-struct GizmoMaterial {
-    color: vec4<f32>,
+struct GizmoShading {
+    rim_strength: f32,
+    rim_power: f32,
+    dash_length: f32,
+    occluded_alpha: f32,
 };
 
 @group(2) @binding(0)
-var<uniform> material: GizmoMaterial;
+var<uniform> color: vec4<f32>;
+@group(2) @binding(1)
+var<uniform> shading: GizmoShading;
 
 struct Vertex {
     @builtin(instance_index) instance_index: u32,
     @location(0) position: vec3<f32>,
+    @location(1) normal: vec3<f32>,
 };
 
 struct VertexOutput {
     @builtin(position) clip_position: vec4<f32>,
+    @location(0) world_position: vec3<f32>,
+    @location(1) world_normal: vec3<f32>,
 };
 
 @vertex
 fn vertex(vertex: Vertex) -> VertexOutput {
     var out: VertexOutput;
-    out.clip_position = mesh_position_local_to_clip(
-        get_world_from_local(vertex.instance_index),
-        vec4<f32>(vertex.position, 1.0),
-    );
+    let world_from_local = get_world_from_local(vertex.instance_index);
+    out.clip_position = mesh_position_local_to_clip(world_from_local, vec4<f32>(vertex.position, 1.0));
+    out.world_position = mesh_position_local_to_world(world_from_local, vec4<f32>(vertex.position, 1.0)).xyz;
+    out.world_normal = mesh_normal_local_to_world(vertex.normal, vertex.instance_index);
     return out;
 }
 
 @fragment
-fn fragment() -> @location(0) vec4<f32> {
-    return material.color;
+fn fragment(in: VertexOutput) -> @location(0) vec4<f32> {
+    var out = color;
+
+    // Brighten the silhouette so the shape of the handle reads without lighting
+    let view_dir = normalize(view.world_position - in.world_position);
+    let facing = abs(dot(normalize(in.world_normal), view_dir));
+    let rim = pow(1.0 - facing, shading.rim_power) * shading.rim_strength;
+    out = vec4<f32>(out.rgb + vec3<f32>(rim), out.a);
+
+#ifdef GIZMO_OCCLUDED
+    // Diagonal stripes for the parts behind scene geometry
+    let stripe = fract((in.clip_position.x + in.clip_position.y) / (2.0 * max(shading.dash_length, 1.0)));
+    if stripe > 0.5 {
+        discard;
+    }
+    out.a = out.a * shading.occluded_alpha;
+#endif
+
+    return out;
 }
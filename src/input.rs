@@ -0,0 +1,204 @@
+use bevy::{input::touch::Touches, prelude::*};
+
+use crate::{
+    constraints::{GizmoConstraints, GizmoLimits},
+    parent_transform, GizmoDragEnded, GizmoDragStarted, GizmoMode, GizmoPickSource, TransformGizmoSettings,
+};
+
+/// Maps pointer buttons and keys to gizmo actions.
+///
+/// Insert this resource before adding the [`TransformGizmoPlugin`](crate::TransformGizmoPlugin)
+/// to override the defaults.
+///
+/// The defaults keep the modifiers of the drag button apart: Ctrl snaps, Alt slows down and Shift adds to the
+/// selection, so a Shift-click that turns into a drag is not slowed down. Shift with the secondary button places the
+/// cursor. The [`GizmoNudge`](crate::nudge::GizmoNudge) modifiers only apply to nudges, which pause while dragging.
+#[derive(Resource, Debug, Clone)]
+pub struct GizmoInputMap {
+    /// Pointer button that drags the gizmo handles.
+    pub drag_button: PointerButton,
+    /// Holding any of these keys inverts [`GizmoSnapping::enabled`](crate::snapping::GizmoSnapping::enabled) for the current drag.
+    pub snap: Vec<KeyCode>,
+    /// Holding any of these keys slows down the manipulation by [`GizmoSnapping::precision`](crate::snapping::GizmoSnapping::precision).
+    pub precision: Vec<KeyCode>,
+    /// Pressing any of these keys aborts the current drag and restores the original transform.
+    pub cancel: Vec<KeyCode>,
+    /// Holding any of these keys while selecting adds to the selection instead of replacing it.
+    ///
+    /// The plugin does not select entities itself, selection systems can query this with [`GizmoInputMap::adds_to_selection`].
+    pub add_to_selection: Vec<KeyCode>,
+    /// Pressing one of these keys switches the gizmo to the paired mode.
+    pub mode_keys: Vec<(KeyCode, GizmoMode)>,
+    /// Pointer button that places the [`GizmoCursor`](crate::cursor::GizmoCursor) on the clicked scene geometry.
+    pub place_cursor_button: PointerButton,
+    /// Keys that have to be held while clicking to place the [`GizmoCursor`](crate::cursor::GizmoCursor), none if empty.
+    pub place_cursor_modifier: Vec<KeyCode>,
+    /// Maps two finger touch gestures to rotation around the view axis and uniform scale of the selected Entity.
+    pub touch_gestures: bool,
+}
+
+impl Default for GizmoInputMap {
+    fn default() -> Self {
+        GizmoInputMap {
+            drag_button: PointerButton::Primary,
+            snap: vec![KeyCode::ControlLeft, KeyCode::ControlRight],
+            precision: vec![KeyCode::AltLeft, KeyCode::AltRight],
+            cancel: vec![KeyCode::Escape],
+            add_to_selection: vec![KeyCode::ShiftLeft, KeyCode::ShiftRight],
+            mode_keys: vec![
+                (KeyCode::KeyW, GizmoMode::Translate),
+                (KeyCode::KeyE, GizmoMode::Rotate),
+                (KeyCode::KeyR, GizmoMode::Scale),
+                (KeyCode::KeyT, GizmoMode::Universal),
+            ],
+            place_cursor_button: PointerButton::Secondary,
+            place_cursor_modifier: vec![KeyCode::ShiftLeft, KeyCode::ShiftRight],
+            touch_gestures: false,
+        }
+    }
+}
+
+impl GizmoInputMap {
+    pub fn snap_pressed(&self, keys: &ButtonInput<KeyCode>) -> bool {
+        keys.any_pressed(self.snap.iter().copied())
+    }
+    pub fn precision_pressed(&self, keys: &ButtonInput<KeyCode>) -> bool {
+        keys.any_pressed(self.precision.iter().copied())
+    }
+    pub fn cancel_just_pressed(&self, keys: &ButtonInput<KeyCode>) -> bool {
+        keys.any_just_pressed(self.cancel.iter().copied())
+    }
+    pub fn adds_to_selection(&self, keys: &ButtonInput<KeyCode>) -> bool {
+        keys.any_pressed(self.add_to_selection.iter().copied())
+    }
+    pub fn place_cursor_modifier_pressed(&self, keys: &ButtonInput<KeyCode>) -> bool {
+        self.place_cursor_modifier.is_empty() || keys.any_pressed(self.place_cursor_modifier.iter().copied())
+    }
+}
+
+/// Switches the gizmo mode when one of the [`GizmoInputMap::mode_keys`] is pressed.
+pub fn switch_mode(
+    keys: Res<ButtonInput<KeyCode>>,
+    input_map: Res<GizmoInputMap>,
+    mut settings: ResMut<TransformGizmoSettings>,
+) {
+    if settings.is_dragging() {
+        return;
+    }
+    let pressed = input_map.mode_keys.iter().find(|(key, _)| keys.just_pressed(*key));
+    if let Some((_, mode)) = pressed {
+        settings.set_mode(*mode);
+    }
+}
+
+/// Proposes rotating and scaling the selected Entity with two finger pinch and twist gestures.
+///
+/// Sends the same drag events as a pointer drag, a gesture ends once fewer than two fingers touch the screen.
+pub fn touch_gestures(
+    touches: Res<Touches>,
+    input_map: Res<GizmoInputMap>,
+    mut settings: ResMut<TransformGizmoSettings>,
+    q_camera: Query<&GlobalTransform, With<GizmoPickSource>>,
+    q_parents: Query<&ChildOf>,
+    q_transform: Query<&GlobalTransform>,
+    q_local_transform: Query<&Transform>,
+    q_constraints: Query<&GizmoConstraints>,
+    q_limits: Query<&GizmoLimits>,
+    mut gesture_start: Local<Option<(Entity, Transform)>>,
+    mut drag_started: EventWriter<GizmoDragStarted>,
+    mut drag_ended: EventWriter<GizmoDragEnded>,
+) {
+    let active_entity = settings
+        .active_entity
+        .filter(|_| input_map.touch_gestures && !settings.is_dragging());
+    let mut active = touches.iter();
+    let fingers = (active.next(), active.next(), active.next());
+
+    // End the gesture once a finger lifts or the selection changed
+    if let Some((entity, start)) = *gesture_start
+        && (!matches!(fingers, (Some(_), Some(_), None)) || active_entity != Some(entity))
+    {
+        *gesture_start = None;
+        if let Ok(transform) = q_local_transform.get(entity) {
+            drag_ended.write(GizmoDragEnded {
+                entity,
+                start,
+                end: *transform,
+                cancelled: false,
+            });
+        }
+    }
+
+    let Some(sel_entity) = active_entity else {
+        return;
+    };
+    let (Some(first), Some(second), None) = fingers else {
+        return;
+    };
+    let Ok(camera_transform) = q_camera.single() else {
+        return;
+    };
+
+    let span = second.position() - first.position();
+    let span_previous = second.previous_position() - first.previous_position();
+    if span.length_squared() <= f32::EPSILON || span_previous.length_squared() <= f32::EPSILON {
+        return;
+    }
+
+    // Screen space has y pointing down, so a positive angle turns clockwise as seen by the camera
+    let angle = span_previous.perp_dot(span).atan2(span_previous.dot(span));
+    let factor = span.length() / span_previous.length();
+    if angle == 0.0 && factor == 1.0 {
+        return;
+    }
+
+    let Ok(selection_transform_local) = q_local_transform.get(sel_entity) else {
+        log::warn!("TransformGizmo: Could not get Transform of selected Entity: {sel_entity:?}");
+        return;
+    };
+    if gesture_start.is_none() {
+        *gesture_start = Some((sel_entity, *selection_transform_local));
+        drag_started.write(GizmoDragStarted {
+            entity: sel_entity,
+            transform: *selection_transform_local,
+        });
+    }
+
+    // The rotation of the Transform is relative to the parent, so turn the view axis into its space
+    let view_axis = camera_transform.forward();
+    let view_axis = parent_transform(sel_entity, &q_parents, &q_transform)
+        .map_or(view_axis, |parent| parent.rotation().inverse() * view_axis);
+    let constraints = q_constraints.get(sel_entity).copied().unwrap_or_default();
+    settings.propose(sel_entity, *selection_transform_local, |proposal| {
+        let rotation = Quat::from_axis_angle(*view_axis, angle);
+        proposal.rotate(constraints.constrain_rotation(rotation));
+        if constraints.scale.all() {
+            proposal.scale *= factor;
+        }
+        if let Ok(limits) = q_limits.get(sel_entity) {
+            *proposal = limits.clamp(*proposal);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn overlaps(a: &[KeyCode], b: &[KeyCode]) -> bool {
+        a.iter().any(|key| b.contains(key))
+    }
+
+    #[test]
+    fn default_drag_modifiers_do_not_overlap() {
+        let input_map = GizmoInputMap::default();
+        assert!(!overlaps(&input_map.snap, &input_map.precision));
+        assert!(!overlaps(&input_map.snap, &input_map.add_to_selection));
+        assert!(!overlaps(&input_map.precision, &input_map.add_to_selection));
+        // Placing the cursor shares Shift with the selection, but not the button
+        assert!(
+            input_map.place_cursor_button != input_map.drag_button
+                || !overlaps(&input_map.place_cursor_modifier, &input_map.add_to_selection)
+        );
+    }
+}
@@ -13,6 +13,10 @@
 
 use bevy::prelude::*;
 use bevy::asset::load_internal_asset;
+use bevy::ecs::entity::EntityHashSet;
+use bevy::picking::{pointer::Location, PickSet};
+use bevy::render::view::VisibilitySystems;
+use bevy::transform::TransformSystem;
 
 pub mod mesh;
 use mesh::*;
@@ -22,8 +26,40 @@ use transformations::*;
 
 pub mod gizmo_component;
 use gizmo_component::*;
+
+pub mod input;
+use input::GizmoInputMap;
+
+pub mod snapping;
+use snapping::GizmoSnapping;
+
+pub mod nudge;
+use nudge::GizmoNudge;
+
+pub mod constraints;
+use constraints::GizmoConstraints;
+
+pub mod validation;
+use validation::GizmoValidator;
+
+pub mod cursor;
+use cursor::{GizmoPivot, MoveSelectionToCursor};
+
+pub mod feedback;
+
+pub mod style;
+use style::{GizmoTheme, TransformGizmoStyle};
+
+pub mod lines;
+use lines::TransformGizmoLines;
+
+pub mod picking;
+use picking::GizmoPickingBackend;
+
+pub mod diagnostics;
 mod gizmo_material;
 use gizmo_material::GizmoMaterial;
+pub use gizmo_material::GizmoShading;
 
 
 #[derive(Component)]
@@ -38,11 +74,175 @@ pub struct GizmoTransformable;
 #[derive(Component)]
 pub struct TransformGizmoPart;
 
+/// An axis of the gizmo, in the space given by its [`GizmoOrientation`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GizmoAxis {
+    X,
+    Y,
+    Z,
+}
+
+impl GizmoAxis {
+    pub const ALL: [GizmoAxis; 3] = [GizmoAxis::X, GizmoAxis::Y, GizmoAxis::Z];
+
+    pub fn vec3(self) -> Vec3 {
+        match self {
+            GizmoAxis::X => Vec3::X,
+            GizmoAxis::Y => Vec3::Y,
+            GizmoAxis::Z => Vec3::Z,
+        }
+    }
+}
+
+/// The manipulation a [`TransformGizmoPart`] performs when dragged.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GizmoHandle {
+    /// Moves along the axis.
+    TranslateAxis(GizmoAxis),
+    /// Moves on the plane normal to the axis.
+    TranslatePlane(GizmoAxis),
+    /// Moves on the plane facing the camera.
+    TranslateView,
+    /// Rotates around the axis.
+    RotateAxis(GizmoAxis),
+    /// Rotates around the view axis of the camera, the handle always faces the camera.
+    RotateView,
+    /// Rotates freely like a trackball, around the view space axis perpendicular to the pointer motion.
+    RotateFree,
+    /// Scales along the axis of the selected Entity.
+    ScaleAxis(GizmoAxis),
+    /// Scales uniformly.
+    ScaleUniform,
+}
+
+impl GizmoHandle {
+    pub fn mode(self) -> GizmoMode {
+        match self {
+            GizmoHandle::TranslateAxis(_) | GizmoHandle::TranslatePlane(_) | GizmoHandle::TranslateView => {
+                GizmoMode::Translate
+            }
+            GizmoHandle::RotateAxis(_) | GizmoHandle::RotateView | GizmoHandle::RotateFree => GizmoMode::Rotate,
+            GizmoHandle::ScaleAxis(_) | GizmoHandle::ScaleUniform => GizmoMode::Scale,
+        }
+    }
+}
+
+/// The set of handles the gizmo shows.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum GizmoMode {
+    Translate,
+    Rotate,
+    Scale,
+    /// Shows every handle except [`GizmoHandle::ScaleUniform`] and [`GizmoHandle::RotateFree`], which would overlap
+    /// the [`GizmoHandle::TranslateView`] sphere and the translation handles.
+    #[default]
+    Universal,
+}
+
+impl GizmoMode {
+    /// Returns whether `handle` is shown in this mode.
+    pub fn shows(self, handle: GizmoHandle) -> bool {
+        match self {
+            GizmoMode::Universal => !matches!(handle, GizmoHandle::ScaleUniform | GizmoHandle::RotateFree),
+            mode => handle.mode() == mode,
+        }
+    }
+}
+
+/// Sent when a drag or nudge of the selected Entity starts.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct GizmoDragStarted {
+    pub entity: Entity,
+    /// Local transform of the Entity before the manipulation.
+    pub transform: Transform,
+}
+
+/// Sent when a drag or nudge of the selected Entity ends.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct GizmoDragEnded {
+    pub entity: Entity,
+    /// Local transform of the Entity before the manipulation.
+    pub start: Transform,
+    /// Local transform of the Entity after the manipulation.
+    pub end: Transform,
+    /// Whether the manipulation was cancelled and `end` was restored to `start`.
+    pub cancelled: bool,
+}
+
+/// Axes the gizmo handles and nudging are aligned to.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum GizmoOrientation {
+    /// Aligned to the world axes.
+    #[default]
+    Global,
+    /// Aligned to the rotation of the selected Entity.
+    Local,
+}
+
 #[derive(Resource, Debug, Default)]
 pub struct TransformGizmoSettings {
     pub(crate) active_entity: Option<Entity>,
     pub(crate) is_dragging: bool,
+    /// Transform of the selected Entity when it was selected, see [`diagnostics::GizmoDiagnosticsPlugin`].
     pub(crate) origin: Option<GlobalTransform>,
+    pub(crate) drag: Option<GizmoDrag>,
+    pub(crate) orientation: GizmoOrientation,
+    pub(crate) mode: GizmoMode,
+    /// Transform waiting for validation, see [`validation::commit_proposal`].
+    pub(crate) proposal: Option<(Entity, Transform)>,
+    pub(crate) rejected: bool,
+    /// World position the selected Entity is snapped onto, see [`snapping::VertexSnapping`].
+    pub(crate) snap_target: Option<Vec3>,
+    /// Alignment guide lines in world space, see [`snapping::AlignmentSnapping`].
+    pub(crate) guides: Vec<(Vec3, Vec3)>,
+    pub(crate) pivot: GizmoPivot,
+    /// Handle being dragged, see [`style::GizmoTheme::active`].
+    pub(crate) active_handle: Option<Entity>,
+}
+
+/// State of an ongoing handle drag.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct GizmoDrag {
+    /// Local transform of the selected Entity when the drag started.
+    pub(crate) start: Transform,
+    /// Accumulated translation before snapping.
+    pub(crate) translation: Vec3,
+    /// Accumulated rotation angle before snapping.
+    pub(crate) rotation: f32,
+    /// Accumulated rotation of the trackball handle.
+    pub(crate) free_rotation: Quat,
+    /// Kind of handle that moves the Entity, `None` until the first proposal.
+    pub(crate) mode: Option<GizmoMode>,
+    /// Angle swept by a rotation ring, see [`feedback::GizmoRotationSector`].
+    pub(crate) sector: Option<feedback::RotationSector>,
+    /// Accumulated scale offset before snapping.
+    pub(crate) scale: Vec3,
+    /// World space offset from the origin of the selected Entity to the point snapped onto vertices.
+    pub(crate) anchor: Vec3,
+    /// Point in the space of the Entity's parent that rotation and scale happen around, see [`GizmoPivot`].
+    pub(crate) pivot: Option<Vec3>,
+    /// Set when the drag was cancelled, remaining drag events are ignored until it ends.
+    pub(crate) cancelled: bool,
+    /// Set when the pointer was released, the drag ends once its last proposal is committed.
+    pub(crate) finished: bool,
+}
+
+impl GizmoDrag {
+    pub(crate) fn new(start: Transform) -> Self {
+        GizmoDrag {
+            start,
+            translation: Vec3::ZERO,
+            rotation: 0.0,
+            free_rotation: Quat::IDENTITY,
+            mode: None,
+            sector: None,
+            scale: Vec3::ZERO,
+            anchor: Vec3::ZERO,
+            pivot: None,
+            cancelled: false,
+            finished: false,
+        }
+    }
 }
 
 impl TransformGizmoSettings {
@@ -52,6 +252,31 @@ impl TransformGizmoSettings {
     pub fn is_dragging(&self) -> bool {
         self.is_dragging
     }
+    pub fn active_entity(&self) -> Option<Entity> {
+        self.active_entity
+    }
+    pub fn orientation(&self) -> GizmoOrientation {
+        self.orientation
+    }
+    pub fn set_orientation(&mut self, orientation: GizmoOrientation) {
+        self.orientation = orientation;
+    }
+    /// Returns whether the last transform proposed for the selected Entity was rejected by the [`GizmoValidator`].
+    pub fn is_rejected(&self) -> bool {
+        self.rejected
+    }
+    pub fn pivot(&self) -> GizmoPivot {
+        self.pivot
+    }
+    pub fn set_pivot(&mut self, pivot: GizmoPivot) {
+        self.pivot = pivot;
+    }
+    pub fn mode(&self) -> GizmoMode {
+        self.mode
+    }
+    pub fn set_mode(&mut self, mode: GizmoMode) {
+        self.mode = mode;
+    }
     pub fn select(&mut self, entity: Entity, origin: GlobalTransform) {
         self.active_entity = Some(entity);
         self.origin = Some(origin);
@@ -60,10 +285,37 @@ impl TransformGizmoSettings {
         self.active_entity = None;
         self.is_dragging = false;
         self.origin = None;
+        self.drag = None;
+        self.active_handle = None;
+        self.proposal = None;
+        self.rejected = false;
+        self.snap_target = None;
+        self.guides.clear();
+    }
+    /// Queues a change to the transform of `entity`, starting from the pending proposal or `current`.
+    pub(crate) fn propose(&mut self, entity: Entity, current: Transform, change: impl FnOnce(&mut Transform)) {
+        let mut transform = match self.proposal {
+            Some((proposal_entity, proposal)) if proposal_entity == entity => proposal,
+            _ => current,
+        };
+        change(&mut transform);
+        self.proposal = Some((entity, transform));
+    }
+}
+
+/// Adds the transform gizmo, configured with the picking backend its handles are hit with.
+#[derive(Debug, Default, Clone)]
+pub struct TransformGizmoPlugin {
+    pub picking: GizmoPickingBackend,
+}
+
+impl TransformGizmoPlugin {
+    pub fn with_picking(mut self, picking: GizmoPickingBackend) -> Self {
+        self.picking = picking;
+        self
     }
 }
 
-pub struct TransformGizmoPlugin;
 impl Plugin for TransformGizmoPlugin {
     fn build(&self, app: &mut App) {
         load_internal_asset!(
@@ -74,56 +326,185 @@ impl Plugin for TransformGizmoPlugin {
         );
 
         app.insert_resource(TransformGizmoSettings::default());
+        app.init_resource::<GizmoInputMap>();
+        app.init_resource::<GizmoSnapping>();
+        app.init_resource::<GizmoNudge>();
+        app.init_resource::<GizmoValidator>();
+        app.init_resource::<TransformGizmoStyle>();
+        app.init_resource::<GizmoTheme>();
+
+        app.add_event::<GizmoDragStarted>();
+        app.add_event::<GizmoDragEnded>();
+        app.add_event::<MoveSelectionToCursor>();
 
-        app.add_plugins(MeshPickingPlugin);
         app.add_plugins(MaterialPlugin::<GizmoMaterial>::default());
+        app.init_gizmo_group::<TransformGizmoLines>();
 
-        app.add_systems(PostStartup, build_gizmo);
+        match self.picking {
+            GizmoPickingBackend::Analytic => {
+                app.add_systems(PreUpdate, (
+                    lines::pick_gizmo_lines,
+                    picking::pick_gizmo_shapes,
+                ).in_set(PickSet::Backend));
+            }
+            GizmoPickingBackend::Mesh => {
+                app.add_systems(PreUpdate, lines::pick_gizmo_lines.in_set(PickSet::Backend));
+                app.add_systems(Update, picking::mark_mesh_pickable);
+            }
+            GizmoPickingBackend::Custom => {}
+        }
+
+        app.add_systems(Startup, cursor::spawn_cursor);
 
-        app.add_systems(Update, debug_print_settings);
+        app.init_resource::<GizmoEnabled>();
+        app.configure_sets(Update, (
+            TransformGizmoSet::Input,
+            TransformGizmoSet::Manipulate,
+            TransformGizmoSet::Sync,
+            TransformGizmoSet::Render,
+        ).chain().run_if(gizmo_enabled));
+        // Placement reads the propagated transforms of this frame, so the gizmo does not trail moving entities
+        app.configure_sets(PostUpdate, (
+            TransformGizmoSet::Sync,
+            TransformGizmoSet::Render,
+        ).chain()
+            .after(TransformSystem::TransformPropagate)
+            .before(VisibilitySystems::CheckVisibility)
+            .run_if(gizmo_enabled));
+        app.add_systems(Update, disable_gizmo);
 
         app.add_systems(Update, (
-            update_gizmo_position,
+            cancel_drag,
+            input::switch_mode,
+            input::touch_gestures,
+            nudge::gizmo_nudge,
+            cursor::place_cursor,
+            cursor::move_selection_to_cursor,
+        ).chain().in_set(TransformGizmoSet::Input));
+
+        app.add_systems(Update, (
+            validation::commit_proposal,
+            feedback::update_sector_angle,
+            finish_drag,
+        ).chain().in_set(TransformGizmoSet::Manipulate));
+
+        app.add_systems(Update, (
+            maintain_gizmo,
             update_gizmo_visibility,
-            deactivate_gizmo_if_entity_does_not_exist
-        ).chain());
+            deactivate_gizmo_if_entity_does_not_exist,
+            style::apply_theme,
+            style::update_handle_materials,
+            add_occluded_parts,
+            sync_occluded_parts,
+            validation::update_rejected_indicator,
+            snapping::update_snap_marker,
+            feedback::update_rotation_sector,
+        ).chain().in_set(TransformGizmoSet::Sync));
+
+        #[cfg(feature = "ui")]
+        app.add_systems(Update, feedback::update_feedback_label.in_set(TransformGizmoSet::Sync));
+
+        app.add_systems(PostUpdate, (
+            update_gizmo_position,
+            update_view_ring,
+            propagate_gizmo_transform,
+        ).chain().in_set(TransformGizmoSet::Sync));
+        app.add_systems(PostUpdate, snapping::update_vertex_candidates.in_set(TransformGizmoSet::Sync));
+
+        app.add_systems(Update, (
+            snapping::draw_alignment_guides,
+            cursor::draw_cursor,
+            feedback::draw_translation_ghost,
+            lines::configure_gizmo_lines,
+        ).chain().in_set(TransformGizmoSet::Render));
+
+        app.add_systems(PostUpdate, lines::draw_gizmo_lines.in_set(TransformGizmoSet::Render));
     }
-}
 
-fn debug_print_settings(
-    gizmo_settings: Res<TransformGizmoSettings>,
-) {
-    if gizmo_settings.is_active() {
-        log::warn!("Active Entity: {:?}", gizmo_settings.active_entity);
-        log::info!("Is Dragging: {}", gizmo_settings.is_dragging);
-        if let Some(origin) = &gizmo_settings.origin {
-            log::info!("Origin: {:?}", origin.translation());
-        } else {
-            log::info!("No Origin Set");
+    fn finish(&self, app: &mut App) {
+        if self.picking == GizmoPickingBackend::Mesh && !app.is_plugin_added::<MeshPickingPlugin>() {
+            log::warn!("TransformGizmo: GizmoPickingBackend::Mesh needs the MeshPickingPlugin, the handles can not be picked");
         }
-        println!();
-    } else {
-        log::info!("Gizmo is not active.");
     }
 }
 
+/// Phases of the gizmo systems in [`Update`], running in this order.
+///
+/// [`TransformGizmoSet::Sync`] and [`TransformGizmoSet::Render`] also run in [`PostUpdate`], after
+/// [`TransformSystem::TransformPropagate`], to place the gizmo at the current pose of the selected Entity.
+///
+/// Handle drags are applied by observers when picking sends its pointer events in [`PreUpdate`], and queue their
+/// result for [`TransformGizmoSet::Manipulate`]. Add run conditions to a set with [`App::configure_sets`].
+#[derive(SystemSet, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TransformGizmoSet {
+    /// Keyboard, touch and gamepad input.
+    Input,
+    /// Validates and applies the proposed transform of the selected Entity.
+    Manipulate,
+    /// Spawns the gizmo, and moves it to the selected Entity in [`PostUpdate`].
+    Sync,
+    /// Immediate mode drawing of guides, the cursor and line handles.
+    Render,
+}
+
+/// Enables or disables the whole gizmo, it is hidden and ignores input while disabled.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GizmoEnabled(pub bool);
+
+impl Default for GizmoEnabled {
+    fn default() -> Self {
+        GizmoEnabled(true)
+    }
+}
+
+/// Run condition that is true while [`GizmoEnabled`] is.
+pub fn gizmo_enabled(enabled: Res<GizmoEnabled>) -> bool {
+    enabled.0
+}
+
+/// Hides the gizmo when it gets disabled, which also keeps its handles from being picked, and cancels the current drag.
+fn disable_gizmo(
+    enabled: Res<GizmoEnabled>,
+    mut settings: ResMut<TransformGizmoSettings>,
+    mut q_local_transform: Query<&mut Transform>,
+    mut drag_ended: EventWriter<GizmoDragEnded>,
+    mut gizmo_query: Query<&mut Visibility, With<TransformGizmo>>,
+) {
+    if !enabled.is_changed() || enabled.0 {
+        return;
+    }
+    // finish_drag does not run while disabled, so the drag can not end normally
+    cancel_active_drag(&mut settings, &mut q_local_transform, &mut drag_ended);
+    for mut visibility in &mut gizmo_query {
+        *visibility = Visibility::Hidden;
+    }
+}
 
 fn update_gizmo_visibility(
     gizmo_settings: Res<TransformGizmoSettings>,
     mut gizmo_query: Query<&mut Visibility, With<TransformGizmo>>,
+    mut handle_query: Query<(&GizmoHandle, &mut Visibility), Without<TransformGizmo>>,
+    constraints_query: Query<&GizmoConstraints>,
 ) {
-    match gizmo_query.single_mut() {
-        Ok(mut visibility) => {
-            *visibility = if gizmo_settings.is_active() {
-                log::info!("Gizmo is active and should be visible.");
-                Visibility::Visible
-            } else {
-                Visibility::Hidden
-            };
-        }
-        Err(err) => {
-            log::error!("Failed to update transform gizmo visibility: {err}");
-        }
+    let constraints = gizmo_settings
+        .active_entity
+        .and_then(|entity| constraints_query.get(entity).ok());
+    for (handle, mut visibility) in &mut handle_query {
+        let allowed = constraints.is_none_or(|constraints| constraints.allows(*handle));
+        visibility.set_if_neq(if gizmo_settings.mode.shows(*handle) && allowed {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        });
+    }
+
+    // The gizmo is spawned lazily, so it may not exist yet
+    if let Ok(mut visibility) = gizmo_query.single_mut() {
+        *visibility = if gizmo_settings.is_active() {
+            Visibility::Visible
+        } else {
+            Visibility::Hidden
+        };
     }
 }
 
@@ -149,11 +530,80 @@ fn update_gizmo_position(
     mut q_gizmo: Query<&mut Transform, With<TransformGizmo>>,
     q_transform: Query<&GlobalTransform>,
 ) {
-    if let Some(active_entity) = gizmo_settings.active_entity {
-        if let Ok(active_transform) = q_transform.get(active_entity) {
-            if let Ok(mut gizmo_transform) = q_gizmo.single_mut() {
-                *gizmo_transform = Transform::from_translation(active_transform.translation());
-            }
+    if let Some(active_entity) = gizmo_settings.active_entity
+        && let Ok(active_transform) = q_transform.get(active_entity)
+        && let Ok(mut gizmo_transform) = q_gizmo.single_mut()
+    {
+        *gizmo_transform = Transform::from_translation(active_transform.translation());
+        if gizmo_settings.orientation == GizmoOrientation::Local {
+            gizmo_transform.rotation = active_transform.rotation();
         }
     }
 }
+
+/// Turns the [`GizmoHandle::RotateView`] ring toward the camera, so it rotates around the view axis.
+fn update_view_ring(
+    q_gizmo: Query<&Transform, With<TransformGizmo>>,
+    q_camera: Query<&GlobalTransform, With<GizmoPickSource>>,
+    mut q_handles: Query<(&GizmoHandle, &mut Transform), Without<TransformGizmo>>,
+) {
+    let (Ok(gizmo_transform), Ok(camera_transform)) = (q_gizmo.single(), q_camera.single()) else {
+        return;
+    };
+    // The ring lies in its local XZ plane, point its Y axis back at the camera
+    let to_camera = gizmo_transform.rotation.inverse() * camera_transform.back();
+    let rotation = Quat::from_rotation_arc(Vec3::Y, *to_camera);
+    for (handle, mut transform) in &mut q_handles {
+        if *handle == GizmoHandle::RotateView && transform.rotation != rotation {
+            transform.rotation = rotation;
+        }
+    }
+}
+
+/// Recomputes the [`GlobalTransform`] of the gizmo and its descendants, which were moved after propagation.
+fn propagate_gizmo_transform(
+    q_gizmo: Query<Entity, With<TransformGizmo>>,
+    q_children: Query<&Children>,
+    q_parents: Query<&ChildOf>,
+    mut q_transforms: Query<(&Transform, &mut GlobalTransform)>,
+) {
+    let Ok(gizmo) = q_gizmo.single() else {
+        return;
+    };
+    if let Ok((transform, mut global_transform)) = q_transforms.get_mut(gizmo) {
+        *global_transform = GlobalTransform::from(*transform);
+    }
+    // Descendants are visited breadth first, so each parent is updated before its children
+    for entity in q_children.iter_descendants(gizmo) {
+        let Ok(parent_transform) = q_parents
+            .get(entity)
+            .and_then(|child_of| q_transforms.get(child_of.parent()).map(|(_, global)| *global))
+        else {
+            continue;
+        };
+        if let Ok((transform, mut global_transform)) = q_transforms.get_mut(entity) {
+            *global_transform = parent_transform.mul_transform(*transform);
+        }
+    }
+}
+
+/// Returns the [`GlobalTransform`] of the parent of `entity`, `None` for root entities.
+pub(crate) fn parent_transform<'a>(
+    entity: Entity,
+    q_parents: &Query<&ChildOf>,
+    q_transform: &'a Query<&GlobalTransform>,
+) -> Option<&'a GlobalTransform> {
+    let child_of = q_parents.get(entity).ok()?;
+    q_transform.get(child_of.parent()).ok()
+}
+
+/// Returns `entity` and all of its descendants, which snapping and placement must not target.
+pub(crate) fn entity_and_descendants(entity: Entity, q_children: &Query<&Children>) -> EntityHashSet {
+    q_children.iter_descendants(entity).chain([entity]).collect()
+}
+
+/// Returns the position of a pointer `location` in the viewport of `camera`, as [`Camera::viewport_to_world`] takes
+/// and [`Camera::world_to_viewport`] returns it. Pointer locations are relative to the whole render target.
+pub(crate) fn viewport_position(camera: &Camera, location: &Location) -> Vec2 {
+    location.position - camera.logical_viewport_rect().map_or(Vec2::ZERO, |viewport| viewport.min)
+}
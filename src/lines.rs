@@ -0,0 +1,187 @@
+use bevy::{
+    ecs::system::EntityCommands,
+    picking::{
+        backend::{HitData, PointerHits},
+        hover::HoverMap,
+        pointer::{PointerId, PointerLocation},
+    },
+    prelude::*,
+    window::PrimaryWindow,
+};
+
+use crate::{
+    picking::{hit_order, GizmoPickShape},
+    style::{GizmoTheme, GizmoThemeColor, TransformGizmoStyle},
+    viewport_position, GizmoHandle, GizmoMaterial, GizmoPickSource, TransformGizmoSettings,
+};
+
+/// How the axes and rotation rings of the gizmo are drawn.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum GizmoRenderMode {
+    /// Capsule and torus meshes.
+    #[default]
+    Meshes,
+    /// Anti-aliased lines of constant screen space width, picked by their distance to the pointer.
+    Lines,
+}
+
+/// Shape of a handle drawn with [`GizmoRenderMode::Lines`], in the local space of the handle.
+#[derive(Component, Debug, Clone, Copy)]
+#[require(Transform, Visibility)]
+pub enum GizmoLine {
+    Segment { start: Vec3, end: Vec3 },
+    /// Arc in the XZ plane starting at +X, like [`TruncatedTorus`](crate::mesh::truncated_torus::TruncatedTorus).
+    Arc { radius: f32, angle: f32 },
+}
+
+impl GizmoLine {
+    /// Segment along the Y axis, like a [`Capsule3d`].
+    pub fn vertical(half_length: f32) -> Self {
+        GizmoLine::Segment {
+            start: Vec3::new(0.0, -half_length, 0.0),
+            end: Vec3::new(0.0, half_length, 0.0),
+        }
+    }
+
+    /// Returns the line as a strip of world space points.
+    fn points(&self, transform: &GlobalTransform) -> Vec<Vec3> {
+        match *self {
+            GizmoLine::Segment { start, end } => {
+                vec![transform.transform_point(start), transform.transform_point(end)]
+            }
+            GizmoLine::Arc { radius, angle } => {
+                let segments = ((angle.abs() / std::f32::consts::TAU * 64.0).ceil() as usize).max(1);
+                (0..=segments)
+                    .map(|segment| {
+                        let theta = angle * segment as f32 / segments as f32;
+                        transform.transform_point(Vec3::new(theta.cos(), 0.0, theta.sin()) * radius)
+                    })
+                    .collect()
+            }
+        }
+    }
+}
+
+/// Gizmo config group of the lines drawn with [`GizmoRenderMode::Lines`].
+#[derive(Default, Reflect, GizmoConfigGroup)]
+pub struct TransformGizmoLines;
+
+/// Gives a handle either its mesh and pick shape or its line, depending on the render mode.
+pub(crate) fn insert_shape(
+    entity: &mut EntityCommands,
+    mode: GizmoRenderMode,
+    line: GizmoLine,
+    mesh: Handle<Mesh>,
+    material: Handle<GizmoMaterial>,
+    pick_shape: GizmoPickShape,
+) {
+    match mode {
+        GizmoRenderMode::Meshes => entity.insert((Mesh3d(mesh), MeshMaterial3d(material), pick_shape)),
+        GizmoRenderMode::Lines => entity.insert(line),
+    };
+}
+
+/// Applies the line width of the [`TransformGizmoStyle`] to the [`TransformGizmoLines`] config group.
+pub fn configure_gizmo_lines(style: Res<TransformGizmoStyle>, mut config_store: ResMut<GizmoConfigStore>) {
+    if !style.is_changed() {
+        return;
+    }
+    let (config, _) = config_store.config_mut::<TransformGizmoLines>();
+    config.line.width = style.line_width;
+    config.line.joints = GizmoLineJoint::Round(4);
+    // Draw over the handle meshes at the same depth
+    config.depth_bias = -0.001;
+}
+
+/// Draws every visible [`GizmoLine`] in the color of its handle state.
+pub fn draw_gizmo_lines(
+    settings: Res<TransformGizmoSettings>,
+    theme: Res<GizmoTheme>,
+    hover_map: Res<HoverMap>,
+    q_lines: Query<(Entity, &GizmoHandle, &GizmoLine, &GlobalTransform, &InheritedVisibility)>,
+    mut gizmos: Gizmos<TransformGizmoLines>,
+) {
+    for (entity, handle, line, transform, visibility) in &q_lines {
+        if !visibility.get() {
+            continue;
+        }
+        let Some(slot) = GizmoThemeColor::of(*handle) else {
+            continue;
+        };
+        let color = match settings.active_handle {
+            Some(active_handle) if active_handle == entity => theme.active,
+            Some(_) => theme.disabled,
+            None if hover_map.values().any(|hovered| hovered.contains_key(&entity)) => theme.hover,
+            None => theme.color(slot),
+        };
+        gizmos.linestrip(line.points(transform), color);
+    }
+}
+
+/// Returns the closest point of the 2D segment from `a` to `b` to `point`, as a fraction along the segment.
+fn closest_fraction(a: Vec2, b: Vec2, point: Vec2) -> f32 {
+    let ab = b - a;
+    let length_squared = ab.length_squared();
+    if length_squared <= f32::EPSILON {
+        return 0.0;
+    }
+    ((point - a).dot(ab) / length_squared).clamp(0.0, 1.0)
+}
+
+/// Picking backend for [`GizmoLine`] handles, hit when the pointer is within
+/// [`TransformGizmoStyle::pick_tolerance`] pixels of the drawn line.
+pub fn pick_gizmo_lines(
+    style: Res<TransformGizmoStyle>,
+    pointers: Query<(&PointerId, &PointerLocation)>,
+    primary_window: Query<Entity, With<PrimaryWindow>>,
+    q_camera: Query<(Entity, &Camera, &GlobalTransform), With<GizmoPickSource>>,
+    q_lines: Query<(Entity, &GizmoLine, &GlobalTransform, &InheritedVisibility)>,
+    mut output: EventWriter<PointerHits>,
+) {
+    let max_distance = style.line_width / 2.0 + style.pick_tolerance;
+    for (pointer_id, pointer_location) in &pointers {
+        let Some(location) = pointer_location.location() else {
+            continue;
+        };
+        for (camera_entity, camera, camera_transform) in &q_camera {
+            if !camera.is_active || !location.is_in_viewport(camera, &primary_window) {
+                continue;
+            }
+            let pointer = viewport_position(camera, location);
+            let mut picks = Vec::new();
+            for (entity, line, transform, visibility) in &q_lines {
+                if !visibility.get() {
+                    continue;
+                }
+                // Closest point of the line to the pointer in screen space
+                let points = line.points(transform);
+                let closest = points
+                    .windows(2)
+                    .filter_map(|window| {
+                        let [start, end] = window else {
+                            return None;
+                        };
+                        let start_2d = camera.world_to_viewport(camera_transform, *start).ok()?;
+                        let end_2d = camera.world_to_viewport(camera_transform, *end).ok()?;
+                        let fraction = closest_fraction(start_2d, end_2d, pointer);
+                        let distance = start_2d.lerp(end_2d, fraction).distance(pointer);
+                        Some((distance, start.lerp(*end, fraction)))
+                    })
+                    .min_by(|(a, _), (b, _)| a.total_cmp(b));
+                let Some((distance, position)) = closest else {
+                    continue;
+                };
+                if distance <= max_distance {
+                    let depth = camera_transform.translation().distance(position);
+                    picks.push((entity, HitData::new(camera_entity, depth, Some(position), None)));
+                }
+            }
+            if picks.is_empty() {
+                continue;
+            }
+            picks.sort_by(|(_, a), (_, b)| a.depth.total_cmp(&b.depth));
+            // Above the scene, so the handles win over the meshes behind them
+            output.write(PointerHits::new(*pointer_id, picks, hit_order(camera, 1)));
+        }
+    }
+}
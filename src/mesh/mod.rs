@@ -1,2 +1,3 @@
 pub mod cone;
-pub mod truncated_torus;
\ No newline at end of file
+pub mod truncated_torus;
+pub mod sector;
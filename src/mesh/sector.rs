@@ -0,0 +1,50 @@
+use bevy::{
+    prelude::*,
+    render::{mesh::Indices, render_asset::RenderAssetUsages, render_resource::PrimitiveTopology},
+};
+/// A flat pie slice in the XZ plane, starting at +X and turning around +Y by `angle`.
+#[derive(Debug, Clone, Copy)]
+pub struct Sector {
+    pub radius: f32,
+    /// Signed angle in radians, negative angles turn the other way around +Y.
+    pub angle: f32,
+    pub subdivisions: usize,
+}
+
+impl Default for Sector {
+    fn default() -> Self {
+        Sector {
+            radius: 1.0,
+            angle: std::f32::consts::PI / 2.0,
+            subdivisions: 64,
+        }
+    }
+}
+
+impl From<Sector> for Mesh {
+    fn from(sector: Sector) -> Self {
+        let subdivisions = sector.subdivisions.max(1);
+        let n_vertices = subdivisions + 2;
+        let mut positions: Vec<[f32; 3]> = Vec::with_capacity(n_vertices);
+        let mut indices: Vec<u32> = Vec::with_capacity(subdivisions * 3);
+
+        positions.push([0.0, 0.0, 0.0]);
+        let stride = sector.angle / subdivisions as f32;
+        for segment in 0..=subdivisions {
+            let point = Quat::from_rotation_y(stride * segment as f32) * Vec3::X * sector.radius;
+            positions.push(point.to_array());
+        }
+        for segment in 1..=subdivisions as u32 {
+            indices.extend_from_slice(&[0, segment, segment + 1]);
+        }
+
+        let mut mesh = Mesh::new(
+            PrimitiveTopology::TriangleList,
+            RenderAssetUsages::default(),
+        );
+        mesh.insert_indices(Indices::U32(indices));
+        mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, vec![[0.0, 1.0, 0.0]; positions.len()]);
+        mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+        mesh
+    }
+}
@@ -39,7 +39,7 @@ impl From<TruncatedTorus> for Mesh {
 
         for segment in 0..=torus.subdivisions_segments {
             let theta = segment_stride * segment as f32;
-            let segment_pos = Vec3::new(theta.cos(), 0.0, theta.sin() * torus.radius);
+            let segment_pos = Vec3::new(theta.cos(), 0.0, theta.sin()) * torus.radius;
 
             for side in 0..=torus.subdivisions_sides {
                 let phi = side_stride * side as f32;
@@ -48,7 +48,8 @@ impl From<TruncatedTorus> for Mesh {
                 let z = theta.sin() * (torus.radius + torus.ring_radius * phi.cos());
                 let y = torus.ring_radius * phi.sin();
 
-                let normal = segment_pos.cross(Vec3::Y).normalize();
+                // Points away from the centreline of the ring
+                let normal = (Vec3::new(x, y, z) - segment_pos).normalize();
 
                 positions.push([x, y, z]);
                 normals.push(normal.into());
@@ -0,0 +1,160 @@
+use bevy::prelude::*;
+
+use crate::{
+    constraints::{GizmoConstraints, GizmoLimits}, transformations::MIN_SCALE_FACTOR, GizmoDragEnded, GizmoDragStarted,
+    GizmoOrientation, TransformGizmoSettings,
+};
+
+/// Keyboard and gamepad nudging of the selected Entity.
+///
+/// Arrow keys nudge along X and Z, Page Up and Page Down along Y. The first connected gamepad nudges
+/// continuously with the left stick along X and Z and the right stick along Y. Nudges follow the
+/// [`GizmoOrientation`] of the gizmo.
+///
+/// Off by default, since these inputs often control the camera. Insert this resource with `enabled` set to turn it on.
+#[derive(Resource, Debug, Clone)]
+pub struct GizmoNudge {
+    pub enabled: bool,
+    /// Distance moved per nudge in world units.
+    pub translation_step: f32,
+    /// Angle rotated per nudge in radians.
+    pub rotation_step: f32,
+    /// Scale added per nudge.
+    pub scale_step: f32,
+    /// Nudges per second while a gamepad stick is fully deflected.
+    pub gamepad_rate: f32,
+    /// Stick deflection below which the gamepad is ignored.
+    pub gamepad_deadzone: f32,
+    /// Holding any of these keys, or the gamepad's left trigger, rotates instead of translating.
+    pub rotate_modifier: Vec<KeyCode>,
+    /// Holding any of these keys, or the gamepad's right trigger, scales instead of translating.
+    pub scale_modifier: Vec<KeyCode>,
+}
+
+impl Default for GizmoNudge {
+    fn default() -> Self {
+        GizmoNudge {
+            enabled: false,
+            translation_step: 0.1,
+            rotation_step: 5f32.to_radians(),
+            scale_step: 0.1,
+            gamepad_rate: 10.0,
+            gamepad_deadzone: 0.2,
+            rotate_modifier: vec![KeyCode::AltLeft, KeyCode::AltRight],
+            scale_modifier: vec![KeyCode::ControlLeft, KeyCode::ControlRight],
+        }
+    }
+}
+
+const NUDGE_KEYS: [(KeyCode, Vec3); 6] = [
+    (KeyCode::ArrowRight, Vec3::X),
+    (KeyCode::ArrowLeft, Vec3::NEG_X),
+    (KeyCode::PageUp, Vec3::Y),
+    (KeyCode::PageDown, Vec3::NEG_Y),
+    (KeyCode::ArrowDown, Vec3::Z),
+    (KeyCode::ArrowUp, Vec3::NEG_Z),
+];
+
+/// Proposes nudges of the selected Entity and sends the same drag events as a pointer drag.
+///
+/// A nudge starts with the first nudge input and ends once all nudge keys are released and the
+/// sticks are back in the deadzone.
+pub fn gizmo_nudge(
+    nudge: Res<GizmoNudge>,
+    mut settings: ResMut<TransformGizmoSettings>,
+    keys: Res<ButtonInput<KeyCode>>,
+    gamepads: Query<&Gamepad>,
+    time: Res<Time>,
+    q_local_transform: Query<&Transform>,
+    q_constraints: Query<&GizmoConstraints>,
+    q_limits: Query<&GizmoLimits>,
+    mut nudge_start: Local<Option<(Entity, Transform)>>,
+    mut drag_started: EventWriter<GizmoDragStarted>,
+    mut drag_ended: EventWriter<GizmoDragEnded>,
+) {
+    let active_entity = settings.active_entity.filter(|_| nudge.enabled && !settings.is_dragging());
+
+    // Discrete steps from the keyboard
+    let mut steps = NUDGE_KEYS
+        .iter()
+        .filter(|(key, _)| keys.just_pressed(*key))
+        .fold(Vec3::ZERO, |sum, (_, direction)| sum + *direction);
+    let mut held = NUDGE_KEYS.iter().any(|(key, _)| keys.pressed(*key));
+    let mut rotate = keys.any_pressed(nudge.rotate_modifier.iter().copied());
+    let mut scale = keys.any_pressed(nudge.scale_modifier.iter().copied());
+
+    // Continuous steps from the gamepad sticks
+    if let Some(gamepad) = gamepads.iter().next() {
+        let deadzone = |value: f32| if value.abs() > nudge.gamepad_deadzone { value } else { 0.0 };
+        let stick = Vec3::new(
+            deadzone(gamepad.left_stick().x),
+            deadzone(gamepad.right_stick().y),
+            -deadzone(gamepad.left_stick().y),
+        );
+        if stick != Vec3::ZERO {
+            steps += stick * nudge.gamepad_rate * time.delta_secs();
+            held = true;
+        }
+        rotate |= gamepad.pressed(GamepadButton::LeftTrigger);
+        scale |= gamepad.pressed(GamepadButton::RightTrigger);
+    }
+
+    // End the nudge once the input is released or the selection changed
+    if let Some((entity, start)) = *nudge_start
+        && (!held || active_entity != Some(entity))
+    {
+        *nudge_start = None;
+        if let Ok(transform) = q_local_transform.get(entity) {
+            drag_ended.write(GizmoDragEnded {
+                entity,
+                start,
+                end: *transform,
+                cancelled: false,
+            });
+        }
+    }
+
+    let Some(sel_entity) = active_entity else {
+        return;
+    };
+    if steps == Vec3::ZERO {
+        return;
+    }
+    let Ok(selection_transform_local) = q_local_transform.get(sel_entity) else {
+        log::warn!("TransformGizmo: Could not get Transform of selected Entity: {sel_entity:?}");
+        return;
+    };
+
+    if nudge_start.is_none() {
+        *nudge_start = Some((sel_entity, *selection_transform_local));
+        drag_started.write(GizmoDragStarted {
+            entity: sel_entity,
+            transform: *selection_transform_local,
+        });
+    }
+
+    let start_scale = nudge_start.map_or(selection_transform_local.scale, |(_, start)| start.scale);
+    let constraints = q_constraints.get(sel_entity).copied().unwrap_or_default();
+    let orientation = settings.orientation();
+    settings.propose(sel_entity, *selection_transform_local, |proposal| {
+        if scale {
+            // Scale is always relative to the Entity's own axes, and stops short of zero like dragging a handle
+            let magnitude = proposal.scale.abs() + constraints.constrain_scale(steps * nudge.scale_step);
+            proposal.scale = magnitude.max(start_scale.abs() * MIN_SCALE_FACTOR) * start_scale.signum();
+        } else {
+            let axes = match orientation {
+                GizmoOrientation::Global => Quat::IDENTITY,
+                GizmoOrientation::Local => proposal.rotation,
+            };
+            if rotate {
+                let rotation = Quat::from_scaled_axis(axes * steps * nudge.rotation_step);
+                proposal.rotate(constraints.constrain_rotation(rotation));
+            } else {
+                proposal.translation += constraints.constrain_translation(axes * steps * nudge.translation_step);
+            }
+        }
+        if let Ok(limits) = q_limits.get(sel_entity) {
+            *proposal = limits.clamp(*proposal);
+        }
+    });
+}
@@ -0,0 +1,298 @@
+use std::f32::consts::TAU;
+
+use bevy::{
+    picking::{
+        backend::{HitData, PointerHits},
+        mesh_picking::MeshPickingCamera,
+        pointer::{PointerId, PointerLocation},
+    },
+    prelude::*,
+    window::PrimaryWindow,
+};
+
+use crate::{style::TransformGizmoStyle, viewport_position, GizmoHandle, GizmoPickSource};
+
+/// Picking backend the [`TransformGizmoPlugin`](crate::TransformGizmoPlugin) hits the gizmo handles with.
+///
+/// With [`Analytic`](Self::Analytic) and [`Custom`](Self::Custom) the plugin picks nothing but the gizmo, add a backend
+/// for the rest of the scene if the app selects entities with the pointer.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum GizmoPickingBackend {
+    /// [`pick_gizmo_shapes`] and [`pick_gizmo_lines`](crate::lines::pick_gizmo_lines), which only see the gizmo.
+    #[default]
+    Analytic,
+    /// Bevy's [`MeshPickingPlugin`], which the app has to add and configure itself.
+    ///
+    /// The plugin picks every mesh in the scene unless the app sets
+    /// [`MeshPickingSettings::require_markers`](bevy::picking::mesh_picking::MeshPickingSettings::require_markers), so
+    /// whether the rest of the scene is picked is up to the app. The handles are marked with [`Pickable`] and the
+    /// [`GizmoPickSource`] camera with [`MeshPickingCamera`], so they are picked either way.
+    Mesh,
+    /// No built-in backend, the app reports hits on the handles itself, for example through a physics engine with
+    /// colliders built from each handle's [`GizmoPickShape`].
+    Custom,
+}
+
+/// Steps of the distance field march along the pointer ray.
+const MAX_STEPS: usize = 64;
+/// Distance along the pointer ray after which the march gives up.
+const MAX_DISTANCE: f32 = 10_000.0;
+
+/// Analytic shape of a handle in its local space, matching its mesh.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GizmoPrimitive {
+    /// Cylinder with round caps along the Y axis, like a [`Capsule3d`].
+    Cylinder { radius: f32, half_length: f32 },
+    /// Cone with its base on the XZ plane and its tip on +Y, like [`Cone`](crate::mesh::cone::Cone).
+    Cone { radius: f32, height: f32 },
+    /// Disk on the XZ plane.
+    Disk { radius: f32 },
+    /// Arc of a torus in the XZ plane starting at +X, like [`TruncatedTorus`](crate::mesh::truncated_torus::TruncatedTorus).
+    Torus { radius: f32, ring_radius: f32, angle: f32 },
+    Sphere { radius: f32 },
+    /// Box centered on the origin, like a [`Cuboid`]. With a zero `half_size.y` it is a rectangle on the XZ plane,
+    /// like a [`Plane3d`] mesh.
+    Cuboid { half_size: Vec3 },
+}
+
+impl GizmoPrimitive {
+    /// Returns the distance from `point` to the surface, negative inside.
+    pub fn distance(&self, point: Vec3) -> f32 {
+        match *self {
+            GizmoPrimitive::Cylinder { radius, half_length } => {
+                let closest = Vec3::new(0.0, point.y.clamp(-half_length, half_length), 0.0);
+                point.distance(closest) - radius
+            }
+            GizmoPrimitive::Cone { radius, height } => {
+                // Distance to the triangle swept around the Y axis
+                let point = Vec2::new(point.xz().length(), point.y);
+                let base = distance_to_segment(point, Vec2::ZERO, Vec2::new(radius, 0.0));
+                let side = distance_to_segment(point, Vec2::new(radius, 0.0), Vec2::new(0.0, height));
+                let inside = point.y >= 0.0 && point.x / radius + point.y / height <= 1.0;
+                let distance = base.min(side);
+                if inside { -distance } else { distance }
+            }
+            GizmoPrimitive::Disk { radius } => {
+                Vec2::new((point.xz().length() - radius).max(0.0), point.y).length()
+            }
+            GizmoPrimitive::Torus { radius, ring_radius, angle } => {
+                let theta = point.z.atan2(point.x).rem_euclid(TAU);
+                if angle >= TAU || theta <= angle {
+                    Vec2::new(point.xz().length() - radius, point.y).length() - ring_radius
+                } else {
+                    // Outside the arc, the closest part is one of its ends
+                    let start = Vec3::X * radius;
+                    let end = Vec3::new(angle.cos(), 0.0, angle.sin()) * radius;
+                    point.distance(start).min(point.distance(end)) - ring_radius
+                }
+            }
+            GizmoPrimitive::Sphere { radius } => point.length() - radius,
+            GizmoPrimitive::Cuboid { half_size } => {
+                let outside = point.abs() - half_size;
+                outside.max(Vec3::ZERO).length() + outside.max_element().min(0.0)
+            }
+        }
+    }
+}
+
+fn distance_to_segment(point: Vec2, start: Vec2, end: Vec2) -> f32 {
+    let segment = end - start;
+    let fraction = ((point - start).dot(segment) / segment.length_squared().max(f32::EPSILON)).clamp(0.0, 1.0);
+    point.distance(start + segment * fraction)
+}
+
+/// Shape a handle is picked with by [`pick_gizmo_shapes`].
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct GizmoPickShape {
+    pub primitive: GizmoPrimitive,
+    /// Hits on shapes with a higher priority are reported above hits on lower ones, regardless of depth.
+    pub priority: u8,
+}
+
+impl GizmoPickShape {
+    pub fn new(primitive: GizmoPrimitive, priority: u8) -> Self {
+        GizmoPickShape { primitive, priority }
+    }
+}
+
+/// Marks new handles and the [`GizmoPickSource`] camera for mesh picking that requires markers.
+pub fn mark_mesh_pickable(
+    mut commands: Commands,
+    q_handles: Query<Entity, (Added<GizmoHandle>, Without<Pickable>)>,
+    q_camera: Query<Entity, (With<GizmoPickSource>, Without<MeshPickingCamera>)>,
+) {
+    for entity in &q_handles {
+        commands.entity(entity).insert(Pickable::default());
+    }
+    for entity in &q_camera {
+        commands.entity(entity).insert(MeshPickingCamera);
+    }
+}
+
+/// Order of the [`PointerHits`] for handles of `priority`, above the scene seen by `camera`.
+pub(crate) fn hit_order(camera: &Camera, priority: u8) -> f32 {
+    camera.order as f32 + 0.5 + f32::from(priority) * 0.01
+}
+
+/// Picking backend for [`GizmoPickShape`] handles, marching the pointer ray through their distance fields.
+///
+/// Shapes are grown by [`TransformGizmoStyle::pick_tolerance`] pixels at the distance they are hit, so thin handles
+/// stay easy to grab. Hits are reported per priority, so small handles win over the larger ones they sit on.
+pub fn pick_gizmo_shapes(
+    style: Res<TransformGizmoStyle>,
+    pointers: Query<(&PointerId, &PointerLocation)>,
+    primary_window: Query<Entity, With<PrimaryWindow>>,
+    q_camera: Query<(Entity, &Camera, &GlobalTransform), With<GizmoPickSource>>,
+    q_shapes: Query<(Entity, &GizmoPickShape, &GlobalTransform, &InheritedVisibility)>,
+    mut output: EventWriter<PointerHits>,
+) {
+    for (pointer_id, pointer_location) in &pointers {
+        let Some(location) = pointer_location.location() else {
+            continue;
+        };
+        for (camera_entity, camera, camera_transform) in &q_camera {
+            if !camera.is_active || !location.is_in_viewport(camera, &primary_window) {
+                continue;
+            }
+            let position = viewport_position(camera, location);
+            let Ok(ray) = camera.viewport_to_world(camera_transform, position) else {
+                continue;
+            };
+            // World size of a pixel grows with the distance along the ray, unless the projection is orthographic
+            let Ok(next_ray) = camera.viewport_to_world(camera_transform, position + Vec2::X) else {
+                continue;
+            };
+            let pixel_offset = ray.origin.distance(next_ray.origin);
+            let pixel_spread = ray.direction.angle_between(*next_ray.direction);
+            let tolerance = |distance: f32| (pixel_offset + pixel_spread * distance) * style.pick_tolerance;
+
+            let mut picks = Vec::new();
+            for (entity, shape, transform, visibility) in &q_shapes {
+                if !visibility.get() {
+                    continue;
+                }
+                if let Some(depth) = march(shape.primitive, transform, ray, tolerance) {
+                    let hit = HitData::new(camera_entity, depth, Some(ray.get_point(depth)), None);
+                    picks.push((shape.priority, entity, hit));
+                }
+            }
+            picks.sort_by(|(priority_a, _, a), (priority_b, _, b)| {
+                priority_a.cmp(priority_b).then(a.depth.total_cmp(&b.depth))
+            });
+            for group in picks.chunk_by(|(a, _, _), (b, _, _)| a == b) {
+                let Some((priority, _, _)) = group.first() else {
+                    continue;
+                };
+                let hits = group.iter().map(|(_, entity, hit)| (*entity, hit.clone())).collect();
+                output.write(PointerHits::new(*pointer_id, hits, hit_order(camera, *priority)));
+            }
+        }
+    }
+}
+
+/// Returns the distance along `ray` at which it comes within `tolerance` of `primitive`.
+fn march(
+    primitive: GizmoPrimitive,
+    transform: &GlobalTransform,
+    ray: Ray3d,
+    tolerance: impl Fn(f32) -> f32,
+) -> Option<f32> {
+    let local_from_world = transform.affine().inverse();
+    let origin = local_from_world.transform_point3(ray.origin);
+    let direction = local_from_world.transform_vector3(*ray.direction);
+    // Local units per world unit along the ray
+    let scale = direction.length();
+    if scale <= f32::EPSILON {
+        return None;
+    }
+    let direction = direction / scale;
+
+    let mut distance = 0.0;
+    for _ in 0..MAX_STEPS {
+        let surface_distance = primitive.distance(origin + direction * distance);
+        if surface_distance <= tolerance(distance / scale) * scale {
+            return Some(distance / scale);
+        }
+        distance += surface_distance;
+        if distance > MAX_DISTANCE * scale {
+            break;
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn near(a: f32, b: f32) -> bool {
+        (a - b).abs() < 1e-3
+    }
+
+    #[test]
+    fn cylinder_distance() {
+        let cylinder = GizmoPrimitive::Cylinder { radius: 0.1, half_length: 1.0 };
+        assert!(near(cylinder.distance(Vec3::new(1.0, 0.5, 0.0)), 0.9));
+        assert!(near(cylinder.distance(Vec3::new(0.0, 2.0, 0.0)), 0.9));
+        assert!(cylinder.distance(Vec3::ZERO) < 0.0);
+    }
+
+    #[test]
+    fn cone_distance() {
+        let cone = GizmoPrimitive::Cone { radius: 1.0, height: 1.0 };
+        assert!(near(cone.distance(Vec3::new(0.0, 2.0, 0.0)), 1.0));
+        assert!(near(cone.distance(Vec3::new(0.5, -1.0, 0.0)), 1.0));
+        assert!(cone.distance(Vec3::new(0.0, 0.25, 0.0)) < 0.0);
+    }
+
+    #[test]
+    fn torus_distance_ends_at_arc() {
+        let torus = GizmoPrimitive::Torus { radius: 1.0, ring_radius: 0.1, angle: TAU / 4.0 };
+        assert!(near(torus.distance(Vec3::new(0.0, 0.0, 1.0)), -0.1));
+        // Past the end of the quarter arc the closest part is its end at +Z
+        assert!(near(torus.distance(Vec3::new(-1.0, 0.0, 1.0)), 0.9));
+        assert!(near(torus.distance(Vec3::new(0.0, 0.0, -1.0)), 2.0_f32.sqrt() - 0.1));
+    }
+
+    #[test]
+    fn cuboid_distance() {
+        let cuboid = GizmoPrimitive::Cuboid { half_size: Vec3::splat(0.5) };
+        assert!(near(cuboid.distance(Vec3::ZERO), -0.5));
+        assert!(near(cuboid.distance(Vec3::new(1.5, 0.0, 0.0)), 1.0));
+        assert!(near(cuboid.distance(Vec3::new(1.5, 1.5, 0.0)), 2.0_f32.sqrt()));
+    }
+
+    #[test]
+    fn plane_corners_are_pickable() {
+        let rectangle = GizmoPrimitive::Cuboid { half_size: Vec3::new(0.5, 0.0, 0.5) };
+        let disk = GizmoPrimitive::Disk { radius: 0.5 };
+        let corner = Vec3::new(0.45, 0.0, 0.45);
+        assert!(rectangle.distance(corner) <= 0.0);
+        assert!(disk.distance(corner) > 0.1);
+        assert!(near(rectangle.distance(Vec3::new(0.0, 0.2, 0.0)), 0.2));
+    }
+
+    #[test]
+    fn march_hits_and_misses() {
+        let sphere = GizmoPrimitive::Sphere { radius: 1.0 };
+        let transform = GlobalTransform::IDENTITY;
+        let hit = march(sphere, &transform, Ray3d::new(Vec3::new(0.0, 0.0, -5.0), Dir3::Z), |_| 0.0);
+        assert!(hit.is_some_and(|distance| near(distance, 4.0)));
+
+        let offset = Ray3d::new(Vec3::new(2.0, 0.0, -5.0), Dir3::Z);
+        assert!(march(sphere, &transform, offset, |_| 0.0).is_none());
+        // The tolerance widens the shape by a world space distance
+        assert!(march(sphere, &transform, offset, |_| 1.5).is_some());
+
+        let away = Ray3d::new(Vec3::new(0.0, 0.0, -5.0), Dir3::NEG_Z);
+        assert!(march(sphere, &transform, away, |_| 0.0).is_none());
+    }
+
+    #[test]
+    fn march_returns_world_distance_for_scaled_shapes() {
+        let sphere = GizmoPrimitive::Sphere { radius: 1.0 };
+        let transform = GlobalTransform::from(Transform::from_scale(Vec3::splat(2.0)));
+        let hit = march(sphere, &transform, Ray3d::new(Vec3::new(0.0, 0.0, -5.0), Dir3::Z), |_| 0.0);
+        assert!(hit.is_some_and(|distance| near(distance, 3.0)));
+    }
+}
@@ -0,0 +1,557 @@
+use bevy::{
+    ecs::system::SystemParam,
+    platform::collections::HashSet,
+    prelude::*,
+    render::{mesh::PrimitiveTopology, primitives::Aabb},
+};
+
+use crate::{
+    entity_and_descendants, input::GizmoInputMap, parent_transform, GizmoAxis, GizmoTransformable, TransformGizmoSettings,
+};
+
+/// Increments used to snap the manipulation of the selected Entity.
+#[derive(Resource, Debug, Clone)]
+pub struct GizmoSnapping {
+    /// Whether snapping is active without holding one of the [`GizmoInputMap::snap`] keys.
+    pub enabled: bool,
+    /// Translation increment in world units.
+    pub translation: f32,
+    /// Rotation increment in radians.
+    pub rotation: f32,
+    /// Scale increment as a fraction of the scale at the start of the drag.
+    pub scale: f32,
+    /// Factor applied to the pointer movement while one of the [`GizmoInputMap::precision`] keys is held.
+    pub precision: f32,
+    /// Drops the Entity onto the scene geometry under the pointer while dragging the center handle.
+    pub surface: Option<SurfaceSnapping>,
+    /// Snaps the Entity onto vertices of other [`GizmoTransformable`] meshes while translating.
+    pub vertex: Option<VertexSnapping>,
+    /// Aligns the bounds of the Entity with the bounds of other [`GizmoTransformable`] entities while translating.
+    pub alignment: Option<AlignmentSnapping>,
+}
+
+/// Alignment of a translated Entity with the [`Aabb`] of other entities with [`GizmoSnapping::alignment`].
+///
+/// The minimum, center and maximum of the bounds are aligned per world axis, and a guide line is drawn for every
+/// aligned axis.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AlignmentSnapping {
+    /// Distance in world units within which bounds are aligned.
+    pub tolerance: f32,
+    pub guide_color: Color,
+}
+
+impl Default for AlignmentSnapping {
+    fn default() -> Self {
+        AlignmentSnapping {
+            tolerance: 0.1,
+            guide_color: Color::srgb(1.0, 0.2, 0.8),
+        }
+    }
+}
+
+/// Snapping of a translated Entity onto vertices of other meshes with [`GizmoSnapping::vertex`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct VertexSnapping {
+    /// Distance in logical pixels within which a vertex attracts the Entity.
+    pub pixel_radius: f32,
+    /// Also snaps to the midpoints of triangle edges.
+    pub edge_midpoints: bool,
+    pub anchor: VertexAnchor,
+}
+
+impl Default for VertexSnapping {
+    fn default() -> Self {
+        VertexSnapping {
+            pixel_radius: 12.0,
+            edge_midpoints: true,
+            anchor: VertexAnchor::Origin,
+        }
+    }
+}
+
+/// The point of the dragged Entity that is snapped onto the target vertex.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum VertexAnchor {
+    /// The origin of the Entity.
+    #[default]
+    Origin,
+    /// The vertex of the Entity's own mesh closest to the pointer when the drag started.
+    PickedVertex,
+}
+
+/// Placement of an Entity dropped onto scene geometry with [`GizmoSnapping::surface`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SurfaceSnapping {
+    pub anchor: SurfaceAnchor,
+    /// Rotates the Entity so its up axis follows the surface normal.
+    pub align_to_normal: bool,
+}
+
+/// The point of an Entity that is placed on the surface.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum SurfaceAnchor {
+    /// The origin of the Entity.
+    #[default]
+    Origin,
+    /// The bottom of the Entity's [`Aabb`], so it rests on the surface.
+    BoundsBottom,
+}
+
+impl Default for GizmoSnapping {
+    fn default() -> Self {
+        GizmoSnapping {
+            enabled: false,
+            translation: 0.5,
+            rotation: 15f32.to_radians(),
+            scale: 0.1,
+            precision: 0.1,
+            surface: None,
+            vertex: None,
+            alignment: None,
+        }
+    }
+}
+
+impl GizmoSnapping {
+    /// Returns whether snapping applies with the given keyboard state.
+    pub fn is_active(&self, input_map: &GizmoInputMap, keys: &ButtonInput<KeyCode>) -> bool {
+        self.enabled != input_map.snap_pressed(keys)
+    }
+    /// Returns the factor to scale pointer movement by with the given keyboard state.
+    pub fn pointer_factor(&self, input_map: &GizmoInputMap, keys: &ButtonInput<KeyCode>) -> f32 {
+        if input_map.precision_pressed(keys) {
+            self.precision
+        } else {
+            1.0
+        }
+    }
+}
+
+/// Rounds `value` to the nearest multiple of `increment`. Non-positive increments disable snapping.
+pub fn snap_value(value: f32, increment: f32) -> f32 {
+    if increment > 0.0 {
+        (value / increment).round() * increment
+    } else {
+        value
+    }
+}
+
+/// Rounds every component of `value` to the nearest multiple of `increment`.
+pub fn snap_vec3(value: Vec3, increment: f32) -> Vec3 {
+    Vec3::new(
+        snap_value(value.x, increment),
+        snap_value(value.y, increment),
+        snap_value(value.z, increment),
+    )
+}
+
+/// Returns how far the bottom of `aabb` lies below the origin along `up`, for an Entity with the given world rotation and scale.
+pub fn bounds_bottom_offset(aabb: &Aabb, rotation: Quat, scale: Vec3, up: Vec3) -> f32 {
+    let center = Vec3::from(aabb.center);
+    let half_extents = Vec3::from(aabb.half_extents);
+    let lowest = [-1.0, 1.0]
+        .into_iter()
+        .flat_map(|x| [-1.0, 1.0].into_iter().map(move |y| (x, y)))
+        .flat_map(|(x, y)| [-1.0, 1.0].into_iter().map(move |z| Vec3::new(x, y, z)))
+        .map(|sign| (rotation * (scale * (center + half_extents * sign))).dot(up))
+        .fold(f32::INFINITY, f32::min);
+    if lowest.is_finite() { -lowest } else { 0.0 }
+}
+
+/// World space vertex snapping candidates of a [`GizmoTransformable`] mesh.
+///
+/// Kept up to date by [`update_vertex_candidates`] while [`GizmoSnapping::vertex`] is set, so drags do not read
+/// the mesh assets on every pointer move.
+#[derive(Component, Debug, Default, Clone)]
+pub struct VertexCandidates {
+    vertices: Vec<Vec3>,
+    edge_midpoints: Vec<Vec3>,
+}
+
+impl VertexCandidates {
+    /// Reads the world positions of the vertices and triangle edge midpoints of `mesh`.
+    fn new(mesh: &Mesh, transform: &GlobalTransform) -> Self {
+        let Some(positions) = mesh.attribute(Mesh::ATTRIBUTE_POSITION).and_then(|positions| positions.as_float3()) else {
+            return VertexCandidates::default();
+        };
+        let vertices: Vec<Vec3> = positions
+            .iter()
+            .map(|position| transform.transform_point(Vec3::from(*position)))
+            .collect();
+        if mesh.primitive_topology() != PrimitiveTopology::TriangleList {
+            return VertexCandidates {
+                vertices,
+                edge_midpoints: Vec::new(),
+            };
+        }
+
+        let triangle_indices: Vec<usize> = match mesh.indices() {
+            Some(indices) => indices.iter().collect(),
+            None => (0..vertices.len()).collect(),
+        };
+        let edge_midpoints = triangle_indices
+            .chunks_exact(3)
+            .flat_map(|triangle| {
+                let corner = |index: usize| triangle.get(index).and_then(|vertex| vertices.get(*vertex)).copied();
+                [(0, 1), (1, 2), (2, 0)]
+                    .into_iter()
+                    .filter_map(move |(a, b)| Some((corner(a)? + corner(b)?) * 0.5))
+            })
+            .collect();
+        VertexCandidates { vertices, edge_midpoints }
+    }
+
+    /// Returns the vertices, followed by the edge midpoints if `edge_midpoints` is set.
+    fn iter(&self, edge_midpoints: bool) -> impl Iterator<Item = Vec3> + '_ {
+        let midpoints = if edge_midpoints { self.edge_midpoints.as_slice() } else { &[] };
+        self.vertices.iter().chain(midpoints).copied()
+    }
+}
+
+/// Rebuilds the [`VertexCandidates`] of [`GizmoTransformable`] meshes that moved or whose mesh changed.
+pub fn update_vertex_candidates(
+    mut commands: Commands,
+    snapping: Res<GizmoSnapping>,
+    meshes: Res<Assets<Mesh>>,
+    mut mesh_events: EventReader<AssetEvent<Mesh>>,
+    mut q_targets: Query<
+        (Entity, Ref<Mesh3d>, Ref<GlobalTransform>, Option<&mut VertexCandidates>),
+        With<GizmoTransformable>,
+    >,
+) {
+    let changed_meshes: HashSet<AssetId<Mesh>> = mesh_events
+        .read()
+        .filter_map(|event| match event {
+            AssetEvent::Added { id } | AssetEvent::Modified { id } | AssetEvent::LoadedWithDependencies { id } => {
+                Some(*id)
+            }
+            _ => None,
+        })
+        .collect();
+    if snapping.vertex.is_none() {
+        return;
+    }
+    // Candidates went stale while vertex snapping was off
+    let rebuild_all = snapping.is_changed();
+    for (entity, mesh, transform, candidates) in &mut q_targets {
+        let stale = rebuild_all || mesh.is_changed() || transform.is_changed() || changed_meshes.contains(&mesh.id());
+        if candidates.is_some() && !stale {
+            continue;
+        }
+        let Some(mesh_asset) = meshes.get(&mesh.0) else {
+            continue;
+        };
+        let rebuilt = VertexCandidates::new(mesh_asset, &transform);
+        match candidates {
+            Some(mut candidates) => *candidates = rebuilt,
+            None => {
+                commands.entity(entity).insert(rebuilt);
+            }
+        }
+    }
+}
+
+/// Finds vertex snapping candidates on the meshes of [`GizmoTransformable`] entities, from their [`VertexCandidates`].
+#[derive(SystemParam)]
+pub struct VertexSnapper<'w, 's> {
+    targets: Query<'w, 's, (Entity, &'static VertexCandidates, &'static GlobalTransform), With<GizmoTransformable>>,
+    transforms: Query<'w, 's, &'static GlobalTransform>,
+    parents: Query<'w, 's, &'static ChildOf>,
+    children: Query<'w, 's, &'static Children>,
+}
+
+impl VertexSnapper<'_, '_> {
+    /// Returns the vertex of `entity`'s mesh closest to `cursor`, relative to the origin of the Entity in world space.
+    pub fn pick_anchor(
+        &self,
+        entity: Entity,
+        cursor: Vec2,
+        camera: &Camera,
+        camera_transform: &GlobalTransform,
+    ) -> Option<Vec3> {
+        let (_, candidates, transform) = self.targets.get(entity).ok()?;
+        candidates
+            .iter(false)
+            .filter_map(|vertex| {
+                let position = camera.world_to_viewport(camera_transform, vertex).ok()?;
+                Some((vertex, position.distance_squared(cursor)))
+            })
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(vertex, _)| vertex - transform.translation())
+    }
+
+    /// Moves `translation`, in the space of the parent of `entity`, so its anchor lands on the closest candidate
+    /// within the pixel radius. Returns the snapped translation and the world position of the candidate.
+    pub fn snap(
+        &self,
+        entity: Entity,
+        translation: Vec3,
+        anchor: Vec3,
+        config: &VertexSnapping,
+        camera: &Camera,
+        camera_transform: &GlobalTransform,
+    ) -> Option<(Vec3, Vec3)> {
+        let parent_transform = parent_transform(entity, &self.parents, &self.transforms);
+        let to_world = |local: Vec3| parent_transform.map_or(local, |parent| parent.transform_point(local));
+        let anchor_world = to_world(translation) + anchor;
+        let anchor_viewport = camera.world_to_viewport(camera_transform, anchor_world).ok()?;
+
+        // Never snap onto the dragged Entity or its descendants
+        let excluded = entity_and_descendants(entity, &self.children);
+        let (target, _) = self
+            .targets
+            .iter()
+            .filter(|(target, _, _)| !excluded.contains(target))
+            .flat_map(|(_, candidates, _)| candidates.iter(config.edge_midpoints))
+            .filter_map(|candidate| {
+                let position = camera.world_to_viewport(camera_transform, candidate).ok()?;
+                let distance = position.distance(anchor_viewport);
+                (distance <= config.pixel_radius).then_some((candidate, distance))
+            })
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))?;
+
+        let origin_world = target - anchor;
+        let origin_local = parent_transform.map_or(origin_world, |parent| {
+            parent.affine().inverse().transform_point3(origin_world)
+        });
+        Some((origin_local, target))
+    }
+}
+
+/// Marks the marker shown on the target of [`GizmoSnapping::vertex`].
+#[derive(Component)]
+pub struct GizmoSnapMarker;
+
+/// Moves the [`GizmoSnapMarker`] onto the current vertex snap target and hides it when there is none.
+pub fn update_snap_marker(
+    settings: Res<TransformGizmoSettings>,
+    mut q_marker: Query<(&mut Transform, &mut Visibility), With<GizmoSnapMarker>>,
+) {
+    for (mut transform, mut visibility) in &mut q_marker {
+        match settings.snap_target.filter(|_| settings.is_dragging()) {
+            Some(target) => {
+                transform.translation = target;
+                visibility.set_if_neq(Visibility::Visible);
+            }
+            None => {
+                visibility.set_if_neq(Visibility::Hidden);
+            }
+        }
+    }
+}
+
+/// Finds alignments between the [`Aabb`] of the dragged Entity and other [`GizmoTransformable`] entities.
+#[derive(SystemParam)]
+pub struct AlignmentSnapper<'w, 's> {
+    targets: Query<'w, 's, (Entity, &'static Aabb, &'static GlobalTransform), With<GizmoTransformable>>,
+    transforms: Query<'w, 's, &'static GlobalTransform>,
+    parents: Query<'w, 's, &'static ChildOf>,
+    children: Query<'w, 's, &'static Children>,
+}
+
+/// Returns the world space bounds of an [`Aabb`].
+fn world_bounds(aabb: &Aabb, transform: &GlobalTransform) -> (Vec3, Vec3) {
+    let center = Vec3::from(aabb.center);
+    let half_extents = Vec3::from(aabb.half_extents);
+    [-1.0, 1.0]
+        .into_iter()
+        .flat_map(|x| [-1.0, 1.0].into_iter().map(move |y| (x, y)))
+        .flat_map(|(x, y)| [-1.0, 1.0].into_iter().map(move |z| Vec3::new(x, y, z)))
+        .map(|sign| transform.transform_point(center + half_extents * sign))
+        .fold((Vec3::INFINITY, Vec3::NEG_INFINITY), |(min, max), corner| (min.min(corner), max.max(corner)))
+}
+
+/// Returns the world space offset that aligns the bounds `min`..`max` with the closest of `others` within `tolerance`
+/// on each axis, and a guide line for every aligned axis.
+fn align_bounds(min: Vec3, max: Vec3, others: &[(Vec3, Vec3)], tolerance: f32) -> Option<(Vec3, Vec<(Vec3, Vec3)>)> {
+    let center = (min + max) * 0.5;
+    let mut adjustment = Vec3::ZERO;
+    let mut guides = Vec::new();
+    for axis in GizmoAxis::ALL.map(GizmoAxis::vec3) {
+        let own = [min, center, max].map(|point| point.dot(axis));
+        let best = others
+            .iter()
+            .flat_map(|(other_min, other_max)| {
+                let other_center = (*other_min + *other_max) * 0.5;
+                [*other_min, other_center, *other_max]
+                    .map(|point| point.dot(axis))
+                    .into_iter()
+                    .flat_map(move |value| own.map(|own_value| (value - own_value, value, other_center)))
+            })
+            .filter(|(offset, _, _)| offset.abs() <= tolerance)
+            .min_by(|(a, _, _), (b, _, _)| a.abs().total_cmp(&b.abs()));
+        if let Some((offset, value, other_center)) = best {
+            adjustment += axis * offset;
+            guides.push((axis, value, other_center));
+        }
+    }
+    if guides.is_empty() {
+        return None;
+    }
+
+    // Draw the guides between both centers, moved onto the aligned plane
+    let aligned_center = center + adjustment;
+    let guides = guides
+        .into_iter()
+        .map(|(axis, value, other_center)| {
+            (
+                aligned_center + axis * (value - aligned_center.dot(axis)),
+                other_center + axis * (value - other_center.dot(axis)),
+            )
+        })
+        .collect();
+    Some((adjustment, guides))
+}
+
+impl AlignmentSnapper<'_, '_> {
+    /// Moves `translation`, in the space of the parent of `entity`, so its bounds align with the bounds of other
+    /// entities. `current` is the translation the Entity's [`GlobalTransform`] was computed with.
+    ///
+    /// Returns the aligned translation and a guide line for every aligned axis.
+    pub fn align(
+        &self,
+        entity: Entity,
+        translation: Vec3,
+        current: Vec3,
+        config: &AlignmentSnapping,
+    ) -> Option<(Vec3, Vec<(Vec3, Vec3)>)> {
+        let (_, aabb, transform) = self.targets.get(entity).ok()?;
+        let parent_transform = parent_transform(entity, &self.parents, &self.transforms);
+        let to_world = |local: Vec3| parent_transform.map_or(local, |parent| parent.affine().transform_vector3(local));
+
+        let (min, max) = world_bounds(aabb, transform);
+        let moved = to_world(translation - current);
+        let (min, max) = (min + moved, max + moved);
+
+        // Never align with the dragged Entity or its descendants
+        let excluded = entity_and_descendants(entity, &self.children);
+        let others: Vec<(Vec3, Vec3)> = self
+            .targets
+            .iter()
+            .filter(|(target, _, _)| !excluded.contains(target))
+            .map(|(_, aabb, transform)| world_bounds(aabb, transform))
+            .collect();
+
+        let (adjustment, guides) = align_bounds(min, max, &others, config.tolerance)?;
+        let to_local = |world: Vec3| {
+            parent_transform.map_or(world, |parent| parent.affine().inverse().transform_vector3(world))
+        };
+        Some((translation + to_local(adjustment), guides))
+    }
+}
+
+/// Draws the alignment guides of the current drag.
+pub fn draw_alignment_guides(
+    settings: Res<TransformGizmoSettings>,
+    snapping: Res<GizmoSnapping>,
+    mut gizmos: Gizmos,
+) {
+    let Some(config) = snapping.alignment.filter(|_| settings.is_dragging()) else {
+        return;
+    };
+    for (start, end) in &settings.guides {
+        gizmos.line(*start, *end, config.guide_color);
+    }
+}
+
+/// Snaps translations onto vertices and aligns bounds, see [`GizmoSnapping::vertex`] and [`GizmoSnapping::alignment`].
+#[derive(SystemParam)]
+pub struct TranslationSnappers<'w, 's> {
+    pub vertex: VertexSnapper<'w, 's>,
+    pub alignment: AlignmentSnapper<'w, 's>,
+}
+
+/// Result of [`TranslationSnappers::snap`].
+#[derive(Debug, Default, Clone)]
+pub struct TranslationSnap {
+    /// Snapped translation in the space of the Entity's parent.
+    pub translation: Option<Vec3>,
+    /// World position of the vertex the Entity snapped onto.
+    pub target: Option<Vec3>,
+    /// Alignment guide lines in world space.
+    pub guides: Vec<(Vec3, Vec3)>,
+}
+
+impl TranslationSnappers<'_, '_> {
+    /// Snaps the proposed `translation` of `entity`. Vertex snapping takes precedence over alignment.
+    pub fn snap(
+        &self,
+        snapping: &GizmoSnapping,
+        entity: Entity,
+        translation: Vec3,
+        current: Vec3,
+        anchor: Vec3,
+        camera: &Camera,
+        camera_transform: &GlobalTransform,
+    ) -> TranslationSnap {
+        let vertex = snapping.vertex.as_ref().and_then(|config| {
+            self.vertex.snap(entity, translation, anchor, config, camera, camera_transform)
+        });
+        if let Some((translation, target)) = vertex {
+            return TranslationSnap {
+                translation: Some(translation),
+                target: Some(target),
+                guides: Vec::new(),
+            };
+        }
+        let aligned = snapping
+            .alignment
+            .as_ref()
+            .and_then(|config| self.alignment.align(entity, translation, current, config));
+        match aligned {
+            Some((translation, guides)) => TranslationSnap {
+                translation: Some(translation),
+                target: None,
+                guides,
+            },
+            None => TranslationSnap::default(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::FRAC_PI_4;
+
+    use super::*;
+
+    fn near(a: Vec3, b: Vec3) -> bool {
+        a.abs_diff_eq(b, 1e-4)
+    }
+
+    #[test]
+    fn snaps_to_increments() {
+        assert!((snap_value(0.74, 0.5) - 0.5).abs() < 1e-6);
+        assert!((snap_value(-0.76, 0.5) + 1.0).abs() < 1e-6);
+        assert!((snap_value(0.74, 0.0) - 0.74).abs() < 1e-6);
+        assert!(near(snap_vec3(Vec3::new(0.26, 1.1, -0.6), 0.25), Vec3::new(0.25, 1.0, -0.5)));
+    }
+
+    #[test]
+    fn bottom_offset_follows_rotation_and_scale() {
+        let aabb = Aabb::from_min_max(Vec3::splat(-0.5), Vec3::splat(0.5));
+        assert!((bounds_bottom_offset(&aabb, Quat::IDENTITY, Vec3::splat(2.0), Vec3::Y) - 1.0).abs() < 1e-4);
+        let rotated = bounds_bottom_offset(&aabb, Quat::from_rotation_z(FRAC_PI_4), Vec3::ONE, Vec3::Y);
+        assert!((rotated - 0.5 * 2.0_f32.sqrt()).abs() < 1e-4);
+    }
+
+    #[test]
+    fn world_bounds_are_transformed() {
+        let aabb = Aabb::from_min_max(Vec3::ZERO, Vec3::new(2.0, 1.0, 1.0));
+        let transform = GlobalTransform::from(Transform::from_xyz(0.0, 0.0, 10.0).with_rotation(Quat::from_rotation_y(FRAC_PI_4 * 2.0)));
+        let (min, max) = world_bounds(&aabb, &transform);
+        assert!(near(min, Vec3::new(0.0, 0.0, 8.0)));
+        assert!(near(max, Vec3::new(1.0, 1.0, 10.0)));
+    }
+
+    #[test]
+    fn aligns_with_closest_bounds_within_tolerance() {
+        let others = [(Vec3::new(1.05, 0.0, 5.0), Vec3::new(2.05, 1.0, 6.0))];
+        let aligned = align_bounds(Vec3::ZERO, Vec3::ONE, &others, 0.1);
+        // X aligns the own max with the other min, Y already matches and Z is out of tolerance
+        assert!(aligned.is_some_and(|(adjustment, guides)| near(adjustment, Vec3::new(0.05, 0.0, 0.0)) && guides.len() == 2));
+        assert!(align_bounds(Vec3::ZERO, Vec3::ONE, &others, 0.01).is_some_and(|(adjustment, guides)| near(adjustment, Vec3::ZERO) && guides.len() == 1));
+        assert!(align_bounds(Vec3::new(0.0, 3.0, 0.0), Vec3::new(1.0, 4.0, 1.0), &others, 0.01).is_none());
+    }
+}
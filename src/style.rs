@@ -0,0 +1,352 @@
+use bevy::{picking::hover::HoverMap, prelude::*};
+
+use crate::{lines::GizmoRenderMode, GizmoAxis, GizmoHandle, GizmoMaterial, GizmoShading, TransformGizmoSettings};
+
+/// Dimensions of the gizmo handles in world units.
+///
+/// Changing this resource at runtime rebuilds the gizmo.
+#[derive(Resource, Debug, Clone, PartialEq)]
+pub struct TransformGizmoStyle {
+    /// Length of the translation axes.
+    pub axis_length: f32,
+    /// Radius of the rotation rings.
+    pub arc_radius: f32,
+    /// Radius of the outer ring that rotates around the view axis.
+    pub view_ring_radius: f32,
+    /// Radius of the trackball sphere for free rotation.
+    pub trackball_radius: f32,
+    /// Radius of the translation axes and rotation rings.
+    pub line_thickness: f32,
+    pub cone_height: f32,
+    pub cone_radius: f32,
+    /// Radius of the center sphere that moves on the view plane.
+    pub center_radius: f32,
+    /// Edge length of the translation planes.
+    pub plane_size: f32,
+    /// Distance from the axes to the near edges of the translation planes.
+    pub plane_offset: f32,
+    /// Edge length of the per axis scale cubes.
+    pub scale_handle_size: f32,
+    /// Distance from the origin to the centers of the per axis scale cubes.
+    pub scale_offset: f32,
+    /// Edge length of the uniform scale cube.
+    pub scale_uniform_size: f32,
+    pub shading: GizmoShading,
+    pub render_mode: GizmoRenderMode,
+    /// Width in pixels of the lines drawn with [`GizmoRenderMode::Lines`].
+    pub line_width: f32,
+    /// Extra distance in pixels around a handle that still picks it.
+    pub pick_tolerance: f32,
+    /// Draws the parts of the handles hidden behind scene geometry as stripes.
+    pub show_occluded: bool,
+}
+
+impl Default for TransformGizmoStyle {
+    fn default() -> Self {
+        TransformGizmoStyle {
+            axis_length: 1.3,
+            arc_radius: 1.0,
+            view_ring_radius: 1.2,
+            trackball_radius: 0.9,
+            line_thickness: 0.04,
+            cone_height: 0.25,
+            cone_radius: 0.10,
+            center_radius: 0.2,
+            plane_size: 1.3 * 0.25,
+            plane_offset: 1.3 * 0.2,
+            scale_handle_size: 0.15,
+            scale_offset: 1.3 * 0.75,
+            scale_uniform_size: 0.3,
+            shading: GizmoShading::default(),
+            render_mode: GizmoRenderMode::Meshes,
+            line_width: 3.0,
+            pick_tolerance: 4.0,
+            show_occluded: true,
+        }
+    }
+}
+
+impl TransformGizmoStyle {
+    /// Scales every dimension by `factor`, e.g. to make the handles easier to hit on touch screens.
+    pub fn scaled(&self, factor: f32) -> Self {
+        TransformGizmoStyle {
+            axis_length: self.axis_length * factor,
+            arc_radius: self.arc_radius * factor,
+            view_ring_radius: self.view_ring_radius * factor,
+            trackball_radius: self.trackball_radius * factor,
+            line_thickness: self.line_thickness * factor,
+            cone_height: self.cone_height * factor,
+            cone_radius: self.cone_radius * factor,
+            center_radius: self.center_radius * factor,
+            plane_size: self.plane_size * factor,
+            plane_offset: self.plane_offset * factor,
+            scale_handle_size: self.scale_handle_size * factor,
+            scale_offset: self.scale_offset * factor,
+            scale_uniform_size: self.scale_uniform_size * factor,
+            ..self.clone()
+        }
+    }
+}
+
+/// Opacity of the translation planes in the built-in themes.
+const PLANE_ALPHA: f32 = 0.5;
+
+/// Colors of the gizmo handles.
+///
+/// Changing this resource at runtime updates the materials of the existing gizmo.
+#[derive(Resource, Debug, Clone, PartialEq)]
+pub struct GizmoTheme {
+    /// Axes, rings and scale handles of the X axis.
+    pub x: Color,
+    pub y: Color,
+    pub z: Color,
+    /// Translation plane normal to the X axis.
+    pub plane_x: Color,
+    pub plane_y: Color,
+    pub plane_z: Color,
+    /// Center sphere, uniform scale handle and view ring.
+    pub center: Color,
+    /// Handle under the pointer.
+    pub hover: Color,
+    /// Handle being dragged.
+    pub active: Color,
+    /// Every other handle while one is dragged.
+    pub disabled: Color,
+}
+
+impl Default for GizmoTheme {
+    fn default() -> Self {
+        let (s, l) = (0.8, 0.6);
+        GizmoTheme {
+            x: Color::hsl(0.0, s, l),
+            y: Color::hsl(120.0, s, l),
+            z: Color::hsl(240.0, s, l),
+            plane_x: Color::hsla(0.0, s, l, PLANE_ALPHA),
+            plane_y: Color::hsla(120.0, s, l, PLANE_ALPHA),
+            plane_z: Color::hsla(240.0, s, l, PLANE_ALPHA),
+            center: Color::hsl(0.0, 0.0, l),
+            hover: Color::hsl(55.0, 0.9, 0.65),
+            active: Color::hsl(45.0, 1.0, 0.6),
+            disabled: Color::hsl(0.0, 0.0, 0.35),
+        }
+    }
+}
+
+impl GizmoTheme {
+    /// Colors close to the Blender viewport gizmo.
+    pub fn blender() -> Self {
+        let x = Color::srgb(1.0, 0.2, 0.32);
+        let y = Color::srgb(0.545, 0.863, 0.0);
+        let z = Color::srgb(0.157, 0.565, 1.0);
+        GizmoTheme {
+            x,
+            y,
+            z,
+            plane_x: x.with_alpha(PLANE_ALPHA),
+            plane_y: y.with_alpha(PLANE_ALPHA),
+            plane_z: z.with_alpha(PLANE_ALPHA),
+            center: Color::srgb(0.8, 0.8, 0.8),
+            hover: Color::srgb(1.0, 1.0, 1.0),
+            active: Color::srgb(1.0, 1.0, 1.0),
+            disabled: Color::srgb(0.3, 0.3, 0.3),
+        }
+    }
+
+    /// Colors close to the Unity scene view handles.
+    pub fn unity() -> Self {
+        let x = Color::srgb(0.859, 0.243, 0.114);
+        let y = Color::srgb(0.604, 0.953, 0.282);
+        let z = Color::srgb(0.227, 0.478, 0.973);
+        GizmoTheme {
+            x,
+            y,
+            z,
+            plane_x: x.with_alpha(PLANE_ALPHA),
+            plane_y: y.with_alpha(PLANE_ALPHA),
+            plane_z: z.with_alpha(PLANE_ALPHA),
+            center: Color::srgb(0.8, 0.8, 0.8),
+            hover: Color::srgb(0.965, 0.949, 0.196),
+            active: Color::srgb(0.965, 0.949, 0.196),
+            disabled: Color::srgb(0.5, 0.5, 0.5),
+        }
+    }
+
+    /// Okabe-Ito colors that stay distinct with deuteranopia and protanopia.
+    pub fn colorblind() -> Self {
+        let x = Color::srgb(0.902, 0.624, 0.0);
+        let y = Color::srgb(0.337, 0.706, 0.914);
+        let z = Color::srgb(0.8, 0.475, 0.655);
+        GizmoTheme {
+            x,
+            y,
+            z,
+            plane_x: x.with_alpha(PLANE_ALPHA),
+            plane_y: y.with_alpha(PLANE_ALPHA),
+            plane_z: z.with_alpha(PLANE_ALPHA),
+            center: Color::srgb(0.8, 0.8, 0.8),
+            hover: Color::srgb(0.941, 0.894, 0.259),
+            active: Color::srgb(1.0, 1.0, 1.0),
+            disabled: Color::srgb(0.35, 0.35, 0.35),
+        }
+    }
+
+    /// Saturated colors for bright scenes and low vision.
+    pub fn high_contrast() -> Self {
+        let x = Color::srgb(1.0, 0.0, 0.0);
+        let y = Color::srgb(0.0, 1.0, 0.0);
+        let z = Color::srgb(0.2, 0.4, 1.0);
+        GizmoTheme {
+            x,
+            y,
+            z,
+            plane_x: x.with_alpha(PLANE_ALPHA),
+            plane_y: y.with_alpha(PLANE_ALPHA),
+            plane_z: z.with_alpha(PLANE_ALPHA),
+            center: Color::srgb(1.0, 1.0, 1.0),
+            hover: Color::srgb(1.0, 1.0, 0.0),
+            active: Color::srgb(0.0, 1.0, 1.0),
+            disabled: Color::srgb(0.25, 0.25, 0.25),
+        }
+    }
+
+    pub fn color(&self, slot: GizmoThemeColor) -> Color {
+        match slot {
+            GizmoThemeColor::Axis(GizmoAxis::X) => self.x,
+            GizmoThemeColor::Axis(GizmoAxis::Y) => self.y,
+            GizmoThemeColor::Axis(GizmoAxis::Z) => self.z,
+            GizmoThemeColor::Plane(GizmoAxis::X) => self.plane_x,
+            GizmoThemeColor::Plane(GizmoAxis::Y) => self.plane_y,
+            GizmoThemeColor::Plane(GizmoAxis::Z) => self.plane_z,
+            GizmoThemeColor::Center => self.center,
+        }
+    }
+}
+
+/// The [`GizmoTheme`] color a handle is drawn with while it is neither hovered nor dragged.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GizmoThemeColor {
+    Axis(GizmoAxis),
+    Plane(GizmoAxis),
+    Center,
+}
+
+impl GizmoThemeColor {
+    /// Returns the color slot of `handle`, or `None` if it is not themed.
+    pub fn of(handle: GizmoHandle) -> Option<Self> {
+        match handle {
+            GizmoHandle::TranslateAxis(axis) | GizmoHandle::RotateAxis(axis) | GizmoHandle::ScaleAxis(axis) => {
+                Some(GizmoThemeColor::Axis(axis))
+            }
+            GizmoHandle::TranslatePlane(axis) => Some(GizmoThemeColor::Plane(axis)),
+            GizmoHandle::TranslateView | GizmoHandle::RotateView | GizmoHandle::ScaleUniform => {
+                Some(GizmoThemeColor::Center)
+            }
+            GizmoHandle::RotateFree => None,
+        }
+    }
+}
+
+/// Materials shared by the themed handles, updated in place when the [`GizmoTheme`] changes.
+#[derive(Resource, Debug, Clone)]
+pub struct GizmoThemeMaterials {
+    x: Handle<GizmoMaterial>,
+    y: Handle<GizmoMaterial>,
+    z: Handle<GizmoMaterial>,
+    plane_x: Handle<GizmoMaterial>,
+    plane_y: Handle<GizmoMaterial>,
+    plane_z: Handle<GizmoMaterial>,
+    center: Handle<GizmoMaterial>,
+    hover: Handle<GizmoMaterial>,
+    active: Handle<GizmoMaterial>,
+    disabled: Handle<GizmoMaterial>,
+}
+
+impl GizmoThemeMaterials {
+    pub(crate) fn new(materials: &mut Assets<GizmoMaterial>, theme: &GizmoTheme, shading: GizmoShading) -> Self {
+        let mut add = |color: Color| materials.add(GizmoMaterial::new(color, shading));
+        GizmoThemeMaterials {
+            x: add(theme.x),
+            y: add(theme.y),
+            z: add(theme.z),
+            plane_x: add(theme.plane_x),
+            plane_y: add(theme.plane_y),
+            plane_z: add(theme.plane_z),
+            center: add(theme.center),
+            hover: add(theme.hover),
+            active: add(theme.active),
+            disabled: add(theme.disabled),
+        }
+    }
+
+    pub(crate) fn base(&self, slot: GizmoThemeColor) -> Handle<GizmoMaterial> {
+        match slot {
+            GizmoThemeColor::Axis(GizmoAxis::X) => self.x.clone(),
+            GizmoThemeColor::Axis(GizmoAxis::Y) => self.y.clone(),
+            GizmoThemeColor::Axis(GizmoAxis::Z) => self.z.clone(),
+            GizmoThemeColor::Plane(GizmoAxis::X) => self.plane_x.clone(),
+            GizmoThemeColor::Plane(GizmoAxis::Y) => self.plane_y.clone(),
+            GizmoThemeColor::Plane(GizmoAxis::Z) => self.plane_z.clone(),
+            GizmoThemeColor::Center => self.center.clone(),
+        }
+    }
+
+    fn update(&self, materials: &mut Assets<GizmoMaterial>, theme: &GizmoTheme) {
+        let slots = [
+            (&self.x, theme.x),
+            (&self.y, theme.y),
+            (&self.z, theme.z),
+            (&self.plane_x, theme.plane_x),
+            (&self.plane_y, theme.plane_y),
+            (&self.plane_z, theme.plane_z),
+            (&self.center, theme.center),
+            (&self.hover, theme.hover),
+            (&self.active, theme.active),
+            (&self.disabled, theme.disabled),
+        ];
+        for (handle, color) in slots {
+            if let Some(material) = materials.get_mut(handle) {
+                material.color = color.into();
+            }
+        }
+    }
+}
+
+/// Updates the colors of the [`GizmoThemeMaterials`] when the [`GizmoTheme`] changes.
+pub fn apply_theme(
+    theme: Res<GizmoTheme>,
+    theme_materials: Option<Res<GizmoThemeMaterials>>,
+    mut materials: ResMut<Assets<GizmoMaterial>>,
+) {
+    if !theme.is_changed() {
+        return;
+    }
+    if let Some(theme_materials) = theme_materials {
+        theme_materials.update(&mut materials, &theme);
+    }
+}
+
+/// Swaps the material of each themed handle between its base color and the hover, active and disabled colors.
+pub fn update_handle_materials(
+    settings: Res<TransformGizmoSettings>,
+    theme_materials: Option<Res<GizmoThemeMaterials>>,
+    hover_map: Res<HoverMap>,
+    mut q_handles: Query<(Entity, &GizmoHandle, &mut MeshMaterial3d<GizmoMaterial>)>,
+) {
+    let Some(theme_materials) = theme_materials else {
+        return;
+    };
+    for (entity, handle, mut material) in &mut q_handles {
+        let Some(slot) = GizmoThemeColor::of(*handle) else {
+            continue;
+        };
+        let target = match settings.active_handle {
+            Some(active_handle) if active_handle == entity => theme_materials.active.clone(),
+            Some(_) => theme_materials.disabled.clone(),
+            None if hover_map.values().any(|hovered| hovered.contains_key(&entity)) => theme_materials.hover.clone(),
+            None => theme_materials.base(slot),
+        };
+        if material.0 != target {
+            material.0 = target;
+        }
+    }
+}
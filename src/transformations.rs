@@ -1,20 +1,62 @@
-use bevy::{prelude::*, window::PrimaryWindow};
-
-use crate::*;
+use bevy::{
+    ecs::system::SystemParam,
+    picking::mesh_picking::ray_cast::{MeshRayCast, MeshRayCastSettings},
+    prelude::*,
+    render::primitives::Aabb,
+};
+
+use crate::{
+    constraints::{GizmoConstraints, GizmoLimits},
+    cursor::{cursor_in_parent_space, GizmoCursor, GizmoPivot},
+    feedback::RotationSector,
+    snapping::{bounds_bottom_offset, snap_value, snap_vec3, SurfaceAnchor, SurfaceSnapping, TranslationSnap, TranslationSnappers, VertexAnchor},
+    *,
+};
+
+
+/// Pointer ray of a drag event, and where it hits the drag plane before and after the event
+struct PlaneDrag {
+    ray: Ray3d,
+    previous: Vec3,
+    current: Vec3,
+}
 
+/// Intersects the pointer ray of `drag` with the plane through `origin` with `normal`
+///
+/// Uses the location carried by the event, so touch and pen pointers work like the mouse.
+fn drag_on_plane(
+    drag: &Pointer<Drag>,
+    camera: &Camera,
+    camera_transform: &GlobalTransform,
+    origin: Vec3,
+    normal: Dir3,
+) -> Option<PlaneDrag> {
+    let plane = InfinitePlane3d::new(normal);
+    let cursor_position = viewport_position(camera, &drag.pointer_location);
+    let ray = camera.viewport_to_world(camera_transform, cursor_position).ok()?;
+    let previous_ray = camera.viewport_to_world(camera_transform, cursor_position - drag.delta).ok()?;
+    let current = ray.get_point(ray.intersect_plane(origin, plane)?);
+    let previous = previous_ray.get_point(previous_ray.intersect_plane(origin, plane)?);
+    Some(PlaneDrag { ray, previous, current })
+}
 
 /// This Observer Function allows to move in the Forward/Back direction of the dragged Entity
 pub fn transform_axis(
     drag: Trigger<Pointer<Drag>>,
-    q_parents: Query<&ChildOf>,
-    q_transform: Query<&mut GlobalTransform>,
-    mut q_local_transform: Query<&mut Transform>,
-    primary_window: Query<&Window, With<PrimaryWindow>>,
+    q_transform: Query<&GlobalTransform>,
+    q_local_transform: Query<&Transform>,
+    q_constraints: Query<&GizmoConstraints>,
+    q_limits: Query<&GizmoLimits>,
     q_camera: Single<(Entity, &Camera), With <GizmoPickSource>>,
     mut settings: ResMut<TransformGizmoSettings>,
+    enabled: Res<GizmoEnabled>,
+    input_map: Res<GizmoInputMap>,
+    snapping: Res<GizmoSnapping>,
+    keys: Res<ButtonInput<KeyCode>>,
+    snappers: TranslationSnappers,
 ) {
     // Check if the correct Mouse Button is pressed
-    if drag.button != PointerButton::Primary {
+    if drag.button != input_map.drag_button || !enabled.0 {
         return;
     }
 
@@ -22,12 +64,6 @@ pub fn transform_axis(
 
     let handle_entity = drag.target();
 
-    let Ok(parent_entity) = q_parents.get(handle_entity) else {
-        log::warn!("TransformGizmo: Could not get Parent of Handle Entity: {handle_entity:?}");
-        return;
-    };
-    let parent_entity = parent_entity.parent();
-
     let Ok(gismo_transform) = q_transform.get(handle_entity) else {
         log::warn!("TransformGizmo: Could not get Transform of Handle Entity: {handle_entity:?}");
         return;
@@ -41,77 +77,43 @@ pub fn transform_axis(
     let direction = gismo_transform.up();
     let direction_plane = gismo_transform.forward();
 
-    let Ok(window) = primary_window.single() else {
-        log::debug!("primary_window.single() failed in transform_axis!");
-        return;
-    };
-    let Some(cursor_position) = window.cursor_position() else {
-        return;
-    };
-
-    // Calculate a ray pointing from the camera into the world based on the cursor's position.
-    let Ok(ray) = camera.viewport_to_world(camera_transform, cursor_position) else {
-        return;
-    };
-
-    // Calculate if and where the ray is hitting the Handle plane.
-    let Some(distance) =
-        ray.intersect_plane(gismo_transform.translation(), InfinitePlane3d::new(direction_plane))
-    else {
-        return;
-    };
-
-    let point = ray.get_point(distance);
-
-    // Get the Point before the Drag
-    let Ok(ray_delta) = camera.viewport_to_world(camera_transform, cursor_position - drag.delta) else {
-        return;
-    };
-
-    // Calculate if and where the ray is hitting the Handle plane.
-    let Some(distance_delta) =
-        ray_delta.intersect_plane(gismo_transform.translation(), InfinitePlane3d::new(direction_plane))
+    let Some(PlaneDrag { previous: point_delta, current: point, .. }) =
+        drag_on_plane(&drag, camera, camera_transform, gismo_transform.translation(), direction_plane)
     else {
         return;
     };
-    let point_delta = ray_delta.get_point(distance_delta);
 
     // Calculate the drag in the correct direction
     let delta_vector = point-point_delta;
     // Calculate the Effect of the mouse movement in the direction of the Handle
     let result = delta_vector.project_onto(*direction);
 
-    // Set the transforamtion
-    if let Ok(mut parent_transform_local) = q_local_transform.get_mut(parent_entity) {
-        parent_transform_local.translation += result;
-    } else {
-        log::warn!("TransformGizmo: Could not get Transform of Parent Entity: {parent_entity:?}");
-    }
-
-    // Set the Transformation to the connected Object
-    if let Some(sel_entity) = settings.active_entity {
-        if let Ok(mut selection_transform_local) = q_local_transform.get_mut(sel_entity) {
-            selection_transform_local.translation += result;
-            settings.is_dragging = true;
-        } else {
-            log::warn!("TransformGizmo: Could not get Transform of selected Entity: {sel_entity:?}");
-        }
-    }
+    let snap = snapping.is_active(&input_map, &keys).then_some(snapping.translation);
+    let factor = snapping.pointer_factor(&input_map, &keys);
+    let refine = |entity, translation, current, anchor| {
+        snappers.snap(&snapping, entity, translation, current, anchor, camera, camera_transform)
+    };
+    apply_translation(result * factor, snap, refine, &q_local_transform, &q_constraints, &q_limits, &mut settings);
 }
 
 
 /// This Observer Function allows to move in the two directions on the Plane created from Forward and Right of the dragged Entity
 pub fn transform_plane(
     drag: Trigger<Pointer<Drag>>,
-    q_parents: Query<&ChildOf>,
-    q_transform: Query<&mut GlobalTransform>,
-    mut q_local_transform: Query<&mut Transform>,
-    primary_window: Query<&Window, With<PrimaryWindow>>,
+    q_transform: Query<&GlobalTransform>,
+    q_local_transform: Query<&Transform>,
+    q_constraints: Query<&GizmoConstraints>,
+    q_limits: Query<&GizmoLimits>,
     q_camera: Single<(Entity, &Camera), With <GizmoPickSource>>,
     mut settings: ResMut<TransformGizmoSettings>,
+    enabled: Res<GizmoEnabled>,
+    input_map: Res<GizmoInputMap>,
+    snapping: Res<GizmoSnapping>,
+    keys: Res<ButtonInput<KeyCode>>,
+    snappers: TranslationSnappers,
 ) {
     // Check if the correct Mouse Button is pressed
-    if drag.button != PointerButton::Primary {
+    if drag.button != input_map.drag_button || !enabled.0 {
         return;
     }
 
@@ -119,12 +121,6 @@ pub fn transform_plane(
 
     let handle_entity = drag.target();
 
-    let Ok(parent_entity) = q_parents.get(handle_entity) else {
-        log::warn!("TransformGizmo: Could not get Parent of Handle Entity: {handle_entity:?}");
-        return;
-    };
-    let parent_entity = parent_entity.parent();
-
     let Ok(gismo_transform) = q_transform.get(handle_entity) else {
         log::warn!("TransformGizmo: Could not get Transform of Handle Entity: {handle_entity:?}");
         return;
@@ -140,77 +136,115 @@ pub fn transform_plane(
 
     let direction_plane = gismo_transform.up();
 
-    let Ok(window) = primary_window.single() else {
-        log::debug!("primary_window.single() failed in transform_plane!");
+    let Some(PlaneDrag { previous: point_delta, current: point, .. }) =
+        drag_on_plane(&drag, camera, camera_transform, gismo_transform.translation(), direction_plane)
+    else {
         return;
     };
-    let Some(cursor_position) = window.cursor_position() else {
-        return;
+
+    // Calculate the drag in the correct direction
+    let delta_vector = point-point_delta;
+    // Calculate the Effect of the mouse movement in the direction of the Handle
+    let result = delta_vector.project_onto(axis_1) + delta_vector.project_onto(axis_2);
+
+    let snap = snapping.is_active(&input_map, &keys).then_some(snapping.translation);
+    let factor = snapping.pointer_factor(&input_map, &keys);
+    let refine = |entity, translation, current, anchor| {
+        snappers.snap(&snapping, entity, translation, current, anchor, camera, camera_transform)
     };
+    apply_translation(result * factor, snap, refine, &q_local_transform, &q_constraints, &q_limits, &mut settings);
+}
+
 
-    // Calculate a ray pointing from the camera into the world based on the cursor's position.
-    let Ok(ray) = camera.viewport_to_world(camera_transform, cursor_position) else {
+/// This Observer Function allows to move in the two directions on the Plane created from the Camera View of the dragged Entity
+pub fn transform_camera_plane(
+    drag: Trigger<Pointer<Drag>>,
+    q_transform: Query<&GlobalTransform>,
+    q_local_transform: Query<&Transform>,
+    q_constraints: Query<&GizmoConstraints>,
+    q_limits: Query<&GizmoLimits>,
+    q_camera: Single<(Entity, &Camera), With <GizmoPickSource>>,
+    mut settings: ResMut<TransformGizmoSettings>,
+    enabled: Res<GizmoEnabled>,
+    input_map: Res<GizmoInputMap>,
+    snapping: Res<GizmoSnapping>,
+    keys: Res<ButtonInput<KeyCode>>,
+    snappers: TranslationSnappers,
+    mut ray_cast: MeshRayCast,
+    q_scene: SceneQueries,
+) {
+    // Check if the correct Mouse Button is pressed
+    if drag.button != input_map.drag_button || !enabled.0 {
         return;
-    };
+    }
 
-    // Calculate if and where the ray is hitting the Handle plane.
-    let Some(distance) =
-        ray.intersect_plane(gismo_transform.translation(), InfinitePlane3d::new(direction_plane))
-    else {
+    let (camera_entity, camera) = *q_camera;
+
+    let handle_entity = drag.target();
+
+    let Ok(gizmo_transform) = q_transform.get(handle_entity) else {
+        log::warn!("TransformGizmo: Could not get Transform of Handle Entity: {handle_entity:?}");
         return;
     };
 
-    let point = ray.get_point(distance);
-
-    // Get the Point before the Drag
-    let Ok(ray_delta) = camera.viewport_to_world(camera_transform, cursor_position - drag.delta) else {
+    let Ok(camera_transform) = q_transform.get(camera_entity) else {
+        log::warn!("TransformGizmo: Could not get Transform of Camera Entity: {camera_entity:?}");
         return;
     };
 
-    // Calculate if and where the ray is hitting the Handle plane.
-    let Some(distance_delta) =
-        ray_delta.intersect_plane(gismo_transform.translation(), InfinitePlane3d::new(direction_plane))
+    let Some(PlaneDrag { ray, previous: point_delta, current: point }) =
+        drag_on_plane(&drag, camera, camera_transform, gizmo_transform.translation(), camera_transform.back())
     else {
         return;
     };
-    let point_delta = ray_delta.get_point(distance_delta);
 
     // Calculate the drag in the correct direction
+
     let delta_vector = point-point_delta;
+
+    let axis_1 = Vec3::from(camera_transform.up());
+    let axis_2 = Vec3::from(camera_transform.right());
+
     // Calculate the Effect of the mouse movement in the direction of the Handle
     let result = delta_vector.project_onto(axis_1) + delta_vector.project_onto(axis_2);
 
-    // Set the transforamtion
-    if let Ok(mut parent_transform_local) = q_local_transform.get_mut(parent_entity) {
-        parent_transform_local.translation += result;
-    } else {
-        log::warn!("TransformGizmo: Could not get Transform of Parent Entity: {parent_entity:?}");
+    // Drop the Entity onto the scene geometry under the cursor, if there is any
+    if let (Some(surface), Some(sel_entity)) = (snapping.surface, settings.active_entity)
+        && let Some((translation, rotation)) =
+            surface_placement(surface, ray, sel_entity, &mut ray_cast, &q_scene, &q_transform)
+    {
+        apply_placement(translation, rotation, &q_local_transform, &q_constraints, &q_limits, &mut settings);
+        return;
     }
 
-    // Set the Transformation to the connected Object
-    if let Some(sel_entity) = settings.active_entity {
-        if let Ok(mut selection_transform_local) = q_local_transform.get_mut(sel_entity) {
-            selection_transform_local.translation += result;
-            settings.is_dragging = true;
-        } else {
-            log::warn!("TransformGizmo: Could not get Transform of selected Entity: {sel_entity:?}");
-        }
-    }
+    let snap = snapping.is_active(&input_map, &keys).then_some(snapping.translation);
+    let factor = snapping.pointer_factor(&input_map, &keys);
+    let refine = |entity, translation, current, anchor| {
+        snappers.snap(&snapping, entity, translation, current, anchor, camera, camera_transform)
+    };
+    apply_translation(result * factor, snap, refine, &q_local_transform, &q_constraints, &q_limits, &mut settings);
 }
 
 
-/// This Observer Function allows to move in the two directions on the Plane created from the Camera View of the dragged Entity
-pub fn transform_camera_plane(
+/// This Observer Function allows to rotate the dragged Entity
+pub fn transform_rotation(
     drag: Trigger<Pointer<Drag>>,
     q_parents: Query<&ChildOf>,
-    q_transform: Query<&mut GlobalTransform>,
-    mut q_local_transform: Query<&mut Transform>,
-    primary_window: Query<&Window, With<PrimaryWindow>>,
+    q_handles: Query<&GizmoHandle>,
+    q_transform: Query<&GlobalTransform>,
+    q_local_transform: Query<&Transform>,
+    q_constraints: Query<&GizmoConstraints>,
+    q_limits: Query<&GizmoLimits>,
     q_camera: Single<(Entity, &Camera), With <GizmoPickSource>>,
     mut settings: ResMut<TransformGizmoSettings>,
+    enabled: Res<GizmoEnabled>,
+    input_map: Res<GizmoInputMap>,
+    snapping: Res<GizmoSnapping>,
+    keys: Res<ButtonInput<KeyCode>>,
+    style: Res<TransformGizmoStyle>,
 ) {
     // Check if the correct Mouse Button is pressed
-    if drag.button != PointerButton::Primary {
+    if drag.button != input_map.drag_button || !enabled.0 {
         return;
     }
 
@@ -218,12 +252,6 @@ pub fn transform_camera_plane(
 
     let handle_entity = drag.target();
 
-    let Ok(parent_entity) = q_parents.get(handle_entity) else {
-        log::warn!("TransformGizmo: Could not get Parent of Handle Entity: {handle_entity:?}");
-        return;
-    };
-    let parent_entity = parent_entity.parent();
-
     let Ok(gizmo_transform) = q_transform.get(handle_entity) else {
         log::warn!("TransformGizmo: Could not get Transform of Handle Entity: {handle_entity:?}");
         return;
@@ -234,83 +262,122 @@ pub fn transform_camera_plane(
         return;
     };
 
-    let Ok(window) = primary_window.single() else {
-        log::debug!("primary_window.single() failed in transform_camera_plane!");
-        return;
-    };
-    let Some(cursor_position) = window.cursor_position() else {
-        return;
-    };
 
-    // Calculate a ray pointing from the camera into the world based on the cursor's position.
-    let Ok(ray) = camera.viewport_to_world(camera_transform, cursor_position) else {
-        return;
-    };
+    let axis_1 = Vec3::from(gizmo_transform.up());
 
-    let direction_plane = camera_transform.back();
-    // Calculate if and where the ray is hitting the Handle plane.
-    let Some(distance) =
-        ray.intersect_plane(gizmo_transform.translation(), InfinitePlane3d::new(direction_plane))
+    let direction_plane = gizmo_transform.up();
+
+    let Some(PlaneDrag { previous: point_delta, current: point, .. }) =
+        drag_on_plane(&drag, camera, camera_transform, gizmo_transform.translation(), direction_plane)
     else {
         return;
     };
 
-    let point = ray.get_point(distance);
+    // Calculate the Effect of the mouse movement around the axis of the Handle
+    let origin = gizmo_transform.translation();
 
-    // Get the Point before the Drag
-    let Ok(ray_delta) = camera.viewport_to_world(camera_transform, cursor_position - drag.delta) else {
-        return;
-    };
+    let dir1 = (point-origin).normalize_or_zero();
+    let dir2 = (point_delta-origin).normalize_or_zero();
+
+    // Signed angle from the previous to the current pointer direction, so turns past ±180° keep accumulating
+    let angle_diff = dir2.cross(dir1).dot(axis_1).atan2(dir2.dot(dir1));
+
+    // A drag starting on the axis has no direction to start the sector at yet
+    if dir2 != Vec3::ZERO
+        && let Some(gizmo_drag) = settings.drag.as_mut()
+    {
+        let radius = match q_handles.get(handle_entity) {
+            Ok(GizmoHandle::RotateView) => style.view_ring_radius,
+            _ => style.arc_radius,
+        };
+        gizmo_drag.sector.get_or_insert(RotationSector {
+            origin,
+            axis: axis_1,
+            start: dir2,
+            radius,
+            angle: 0.0,
+        });
+    }
 
-    // Calculate if and where the ray is hitting the Handle plane.
-    let Some(distance_delta) =
-        ray_delta.intersect_plane(gizmo_transform.translation(), InfinitePlane3d::new(direction_plane))
-    else {
+    // The rotation of the Transform is relative to the parent, so turn the world axis into its space
+    let Some(sel_entity) = settings.active_entity else {
         return;
     };
-    let point_delta = ray_delta.get_point(distance_delta);
-
-    // Calculate the drag in the correct direction
-
-    let delta_vector = point-point_delta;
+    let axis = parent_transform(sel_entity, &q_parents, &q_transform)
+        .map_or(axis_1, |parent| parent.rotation().inverse() * axis_1);
 
-    let axis_1 = Vec3::from(camera_transform.up());
-    let axis_2 = Vec3::from(camera_transform.right());
+    let snap = snapping.is_active(&input_map, &keys).then_some(snapping.rotation);
+    let factor = snapping.pointer_factor(&input_map, &keys);
+    apply_rotation(axis, angle_diff * factor, snap, &q_local_transform, &q_constraints, &q_limits, &mut settings);
+}
 
-    // Calculate the Effect of the mouse movement in the direction of the Handle
-    let result = delta_vector.project_onto(axis_1) + delta_vector.project_onto(axis_2);
+/// Pixels the pointer has to move for the trackball handle to rotate by one radian
+const TRACKBALL_PIXELS: f32 = 200.0;
 
-    // Set the transforamtion
-    if let Ok(mut parent_transform_local) = q_local_transform.get_mut(parent_entity) {
-        parent_transform_local.translation += result;
-    } else {
-        log::warn!("TransformGizmo: Could not get Transform of Parent Entity: {parent_entity:?}");
+/// This Observer Function allows to rotate the dragged Entity freely like a trackball
+pub fn transform_free_rotation(
+    drag: Trigger<Pointer<Drag>>,
+    q_parents: Query<&ChildOf>,
+    q_transform: Query<&GlobalTransform>,
+    q_local_transform: Query<&Transform>,
+    q_constraints: Query<&GizmoConstraints>,
+    q_limits: Query<&GizmoLimits>,
+    q_camera: Single<Entity, (With<Camera>, With<GizmoPickSource>)>,
+    mut settings: ResMut<TransformGizmoSettings>,
+    enabled: Res<GizmoEnabled>,
+    input_map: Res<GizmoInputMap>,
+    snapping: Res<GizmoSnapping>,
+    keys: Res<ButtonInput<KeyCode>>,
+) {
+    // Check if the correct Mouse Button is pressed
+    if drag.button != input_map.drag_button || !enabled.0 {
+        return;
     }
 
-    // Set the Transformation to the connected Object
-    if let Some(sel_entity) = settings.active_entity {
-        if let Ok(mut selection_transform_local) = q_local_transform.get_mut(sel_entity) {
-            selection_transform_local.translation += result;
-            settings.is_dragging = true;
-        } else {
-            log::warn!("TransformGizmo: Could not get Transform of selected Entity: {sel_entity:?}");
-        }
-    }
+    let camera_entity = *q_camera;
+    let Ok(camera_transform) = q_transform.get(camera_entity) else {
+        log::warn!("TransformGizmo: Could not get Transform of Camera Entity: {camera_entity:?}");
+        return;
+    };
+
+    // Screen space y points down, so dragging down turns the front of the Entity down around the camera's right axis
+    let Some(axis) = (camera_transform.rotation() * Vec3::new(drag.delta.y, drag.delta.x, 0.0)).try_normalize() else {
+        return;
+    };
+    // The rotation of the Transform is relative to the parent, so turn the world axis into its space
+    let Some(sel_entity) = settings.active_entity else {
+        return;
+    };
+    let axis = parent_transform(sel_entity, &q_parents, &q_transform)
+        .map_or(axis, |parent| parent.rotation().inverse() * axis);
+    let angle = drag.delta.length() / TRACKBALL_PIXELS * snapping.pointer_factor(&input_map, &keys);
+    apply_free_rotation(Quat::from_axis_angle(axis, angle), &q_local_transform, &q_constraints, &q_limits, &mut settings);
 }
 
+/// Pixels the pointer has to move for the uniform scale handle to double the scale
+const UNIFORM_SCALE_PIXELS: f32 = 200.0;
 
-/// This Observer Function allows to rotate the dragged Entity
-pub fn transform_rotation(
+/// Smallest factor a manipulation scales the Entity by, so its scale never reaches zero or flips sign
+pub(crate) const MIN_SCALE_FACTOR: f32 = 0.001;
+
+/// This Observer Function allows to scale the dragged Entity along one of its axes, or uniformly
+pub fn transform_scale(
     drag: Trigger<Pointer<Drag>>,
     q_parents: Query<&ChildOf>,
-    q_transform: Query<&mut GlobalTransform>,
-    mut q_local_transform: Query<&mut Transform>,
-    primary_window: Query<&Window, With<PrimaryWindow>>,
+    q_handles: Query<&GizmoHandle>,
+    q_transform: Query<&GlobalTransform>,
+    q_local_transform: Query<&Transform>,
+    q_constraints: Query<&GizmoConstraints>,
+    q_limits: Query<&GizmoLimits>,
     q_camera: Single<(Entity, &Camera), With <GizmoPickSource>>,
     mut settings: ResMut<TransformGizmoSettings>,
+    enabled: Res<GizmoEnabled>,
+    input_map: Res<GizmoInputMap>,
+    snapping: Res<GizmoSnapping>,
+    keys: Res<ButtonInput<KeyCode>>,
 ) {
     // Check if the correct Mouse Button is pressed
-    if drag.button != PointerButton::Primary {
+    if drag.button != input_map.drag_button || !enabled.0 {
         return;
     }
 
@@ -318,6 +385,25 @@ pub fn transform_rotation(
 
     let handle_entity = drag.target();
 
+    let Ok(handle) = q_handles.get(handle_entity) else {
+        log::warn!("TransformGizmo: Could not get GizmoHandle of Handle Entity: {handle_entity:?}");
+        return;
+    };
+
+    let snap = snapping.is_active(&input_map, &keys).then_some(snapping.scale);
+    let factor = snapping.pointer_factor(&input_map, &keys);
+
+    match *handle {
+        GizmoHandle::ScaleAxis(_) => {}
+        GizmoHandle::ScaleUniform => {
+            // Dragging up and to the right grows the Entity
+            let amount = (drag.delta.x - drag.delta.y) / UNIFORM_SCALE_PIXELS;
+            apply_scale(Vec3::splat(amount * factor), snap, &q_local_transform, &q_constraints, &q_limits, &mut settings);
+            return;
+        }
+        _ => return,
+    }
+
     let Ok(parent_entity) = q_parents.get(handle_entity) else {
         log::warn!("TransformGizmo: Could not get Parent of Handle Entity: {handle_entity:?}");
         return;
@@ -329,78 +415,369 @@ pub fn transform_rotation(
         return;
     };
 
+    let Ok(origin_transform) = q_transform.get(parent_entity) else {
+        log::warn!("TransformGizmo: Could not get Transform of Parent Entity: {parent_entity:?}");
+        return;
+    };
+
     let Ok(camera_transform) = q_transform.get(camera_entity) else {
         log::warn!("TransformGizmo: Could not get Transform of Camera Entity: {camera_entity:?}");
         return;
     };
 
+    let direction = gizmo_transform.up();
+    let direction_plane = gizmo_transform.forward();
 
-    let axis_1 = Vec3::from(gizmo_transform.up());
+    let Some(PlaneDrag { previous: point_delta, current: point, .. }) =
+        drag_on_plane(&drag, camera, camera_transform, gizmo_transform.translation(), direction_plane)
+    else {
+        return;
+    };
 
-    let direction_plane = gizmo_transform.up();
+    // Moving the handle by its own distance from the gizmo origin doubles the scale
+    let handle_distance = gizmo_transform.translation().distance(origin_transform.translation());
+    if handle_distance <= f32::EPSILON {
+        return;
+    }
+    let amount = (point - point_delta).dot(*direction) / handle_distance;
 
-    let Ok(window) = primary_window.single() else {
-        log::debug!("primary_window.get_single() failed in transform_rotation!");
+    let Some(sel_entity) = settings.active_entity else {
         return;
     };
-    let Some(cursor_position) = window.cursor_position() else {
+    let Ok(selection_transform) = q_transform.get(sel_entity) else {
+        log::warn!("TransformGizmo: Could not get Transform of selected Entity: {sel_entity:?}");
         return;
     };
+    // Scale follows the Entity's own axes, while the handles follow the world axes under the Global orientation
+    let axis = nearest_local_axis(selection_transform.rotation(), *direction);
+
+    apply_scale(axis * amount * factor, snap, &q_local_transform, &q_constraints, &q_limits, &mut settings);
+}
+
+/// Returns the local axis of an Entity with the world `rotation` that is closest to parallel to `direction`
+fn nearest_local_axis(rotation: Quat, direction: Vec3) -> Vec3 {
+    let local = (rotation.inverse() * direction).abs();
+    if local.x >= local.y && local.x >= local.z {
+        Vec3::X
+    } else if local.y >= local.z {
+        Vec3::Y
+    } else {
+        Vec3::Z
+    }
+}
+
+/// Queries used to look at the scene around the selected Entity
+#[derive(SystemParam)]
+pub struct SceneQueries<'w, 's> {
+    parts: Query<'w, 's, (), With<TransformGizmoPart>>,
+    children: Query<'w, 's, &'static Children>,
+    parents: Query<'w, 's, &'static ChildOf>,
+    aabbs: Query<'w, 's, &'static Aabb>,
+}
+
+/// Finds where the selected Entity lands on the scene geometry hit by `ray`, in the space of its parent
+fn surface_placement(
+    surface: SurfaceSnapping,
+    ray: Ray3d,
+    sel_entity: Entity,
+    ray_cast: &mut MeshRayCast,
+    q_scene: &SceneQueries,
+    q_transform: &Query<&GlobalTransform>,
+) -> Option<(Vec3, Option<Quat>)> {
+    // Skip the dragged Entity, its descendants and the gizmo itself
+    let excluded = entity_and_descendants(sel_entity, &q_scene.children);
+    let filter = |entity: Entity| !excluded.contains(&entity) && !q_scene.parts.contains(entity);
+    let ray_cast_settings = MeshRayCastSettings::default().with_filter(&filter);
+    let (_, hit) = ray_cast.cast_ray(ray, &ray_cast_settings).first()?;
+
+    let selection_transform = q_transform.get(sel_entity).ok()?;
+    let (scale, rotation, _) = selection_transform.to_scale_rotation_translation();
+    let normal = hit.normal.normalize_or(Vec3::Y);
+
+    let (up, aligned) = if surface.align_to_normal {
+        let current_up = (rotation * Vec3::Y).normalize_or(Vec3::Y);
+        (normal, Some(Quat::from_rotation_arc(current_up, normal) * rotation))
+    } else {
+        (Vec3::Y, None)
+    };
+    let lift = match surface.anchor {
+        SurfaceAnchor::Origin => 0.0,
+        SurfaceAnchor::BoundsBottom => q_scene.aabbs.get(sel_entity).map_or(0.0, |aabb| {
+            bounds_bottom_offset(aabb, aligned.unwrap_or(rotation), scale, up)
+        }),
+    };
+    let translation = hit.point + up * lift;
+
+    match parent_transform(sel_entity, &q_scene.parents, q_transform) {
+        Some(parent_transform) => {
+            let (_, parent_rotation, _) = parent_transform.to_scale_rotation_translation();
+            Some((
+                parent_transform.affine().inverse().transform_point3(translation),
+                aligned.map(|aligned| parent_rotation.inverse() * aligned),
+            ))
+        }
+        None => Some((translation, aligned)),
+    }
+}
+
+/// Starts or continues the drag of the selected Entity by a handle of `mode`. Returns the Entity, its local
+/// [`Transform`], the drag and the [`GizmoConstraints`] and [`GizmoLimits`] of the Entity, or `None` if nothing is
+/// selected or the drag was cancelled.
+fn begin_drag<'s, 'q>(
+    mode: GizmoMode,
+    settings: &'s mut TransformGizmoSettings,
+    q_local_transform: &Query<&Transform>,
+    q_constraints: &'q Query<&GizmoConstraints>,
+    q_limits: &'q Query<&GizmoLimits>,
+) -> Option<(Entity, Transform, &'s mut GizmoDrag, Option<&'q GizmoConstraints>, Option<&'q GizmoLimits>)> {
+    let sel_entity = settings.active_entity?;
+    let Ok(selection_transform_local) = q_local_transform.get(sel_entity) else {
+        log::warn!("TransformGizmo: Could not get Transform of selected Entity: {sel_entity:?}");
+        return None;
+    };
+    let drag = settings.drag.get_or_insert_with(|| GizmoDrag::new(*selection_transform_local));
+    if drag.cancelled {
+        return None;
+    }
+    drag.mode = Some(mode);
+    Some((
+        sel_entity,
+        *selection_transform_local,
+        drag,
+        q_constraints.get(sel_entity).ok(),
+        q_limits.get(sel_entity).ok(),
+    ))
+}
+
+/// Returns the translation that swings the Entity around the pivot of `drag` by the rotation that was actually applied,
+/// from the start of the drag to `rotation`
+fn swing_about_pivot(drag: &GizmoDrag, rotation: Quat, limits: Option<&GizmoLimits>) -> Option<Vec3> {
+    let pivot = drag.pivot?;
+    let translation = pivot + (rotation * drag.start.rotation.inverse()) * (drag.start.translation - pivot);
+    Some(limits.map_or(translation, |limits| limits.clamp_translation(translation)))
+}
 
-    // Calculate a ray pointing from the camera into the world based on the cursor's position.
-    let Ok(ray) = camera.viewport_to_world(camera_transform, cursor_position) else {
+/// Proposes moving the selected Entity by the accumulated drag, snapped to `snap` if given
+///
+/// `refine` snaps the proposed translation of the Entity onto vertices or aligned bounds, given its current translation
+/// and the drag anchor.
+fn apply_translation(
+    delta: Vec3,
+    snap: Option<f32>,
+    refine: impl FnOnce(Entity, Vec3, Vec3, Vec3) -> TranslationSnap,
+    q_local_transform: &Query<&Transform>,
+    q_constraints: &Query<&GizmoConstraints>,
+    q_limits: &Query<&GizmoLimits>,
+    settings: &mut TransformGizmoSettings,
+) {
+    let Some((sel_entity, selection_transform_local, drag, constraints, limits)) =
+        begin_drag(GizmoMode::Translate, settings, q_local_transform, q_constraints, q_limits)
+    else {
         return;
     };
 
-    // Calculate if and where the ray is hitting the Handle plane.
-    let Some(distance) =
-        ray.intersect_plane(gizmo_transform.translation(), InfinitePlane3d::new(direction_plane))
+    drag.translation += delta;
+    let offset = match snap {
+        Some(increment) => snap_vec3(drag.translation, increment),
+        None => drag.translation,
+    };
+    let refined = refine(sel_entity, drag.start.translation + offset, selection_transform_local.translation, drag.anchor);
+    let offset = refined.translation.map_or(offset, |translation| translation - drag.start.translation);
+    let offset = constraints.map_or(offset, |constraints| constraints.constrain_translation(offset));
+    let translation = drag.start.translation + offset;
+    let translation = limits.map_or(translation, |limits| limits.clamp_translation(translation));
+
+    settings.snap_target = refined.target;
+    settings.guides = refined.guides;
+    settings.propose(sel_entity, selection_transform_local, |proposal| proposal.translation = translation);
+    settings.is_dragging = true;
+}
+
+/// Proposes placing the selected Entity at `translation`, and optionally `rotation`, in the space of its parent
+fn apply_placement(
+    translation: Vec3,
+    rotation: Option<Quat>,
+    q_local_transform: &Query<&Transform>,
+    q_constraints: &Query<&GizmoConstraints>,
+    q_limits: &Query<&GizmoLimits>,
+    settings: &mut TransformGizmoSettings,
+) {
+    let Some((sel_entity, selection_transform_local, drag, constraints, limits)) =
+        begin_drag(GizmoMode::Translate, settings, q_local_transform, q_constraints, q_limits)
     else {
         return;
     };
 
-    let point = ray.get_point(distance);
+    // Continue regular dragging from the placed position
+    drag.translation = translation - drag.start.translation;
+    let offset = constraints.map_or(drag.translation, |constraints| constraints.constrain_translation(drag.translation));
+    let translation = drag.start.translation + offset;
+    let translation = limits.map_or(translation, |limits| limits.clamp_translation(translation));
+
+    let start_rotation = drag.start.rotation;
+    let rotation = rotation.map(|rotation| {
+        let delta = rotation * start_rotation.inverse();
+        let rotation = constraints.map_or(delta, |constraints| constraints.constrain_rotation(delta)) * start_rotation;
+        limits.map_or(rotation, |limits| limits.clamp_rotation(rotation))
+    });
+
+    settings.propose(sel_entity, selection_transform_local, |proposal| {
+        proposal.translation = translation;
+        if let Some(rotation) = rotation {
+            proposal.rotation = rotation;
+        }
+    });
+    settings.is_dragging = true;
+}
+
+/// Proposes rotating the selected Entity around `axis`, in the space of its parent, by the accumulated drag, snapped to
+/// `snap` if given
+fn apply_rotation(
+    axis: Vec3,
+    angle: f32,
+    snap: Option<f32>,
+    q_local_transform: &Query<&Transform>,
+    q_constraints: &Query<&GizmoConstraints>,
+    q_limits: &Query<&GizmoLimits>,
+    settings: &mut TransformGizmoSettings,
+) {
+    let Some((sel_entity, selection_transform_local, drag, constraints, limits)) =
+        begin_drag(GizmoMode::Rotate, settings, q_local_transform, q_constraints, q_limits)
+    else {
+        return;
+    };
 
-    // Get the Point before the Drag
-    let Ok(ray_delta) = camera.viewport_to_world(camera_transform, cursor_position - drag.delta) else {
+    let axis = constraints.map_or(axis, |constraints| constraints.constrain_rotation_axis(axis));
+    if axis == Vec3::ZERO {
         return;
+    }
+
+    drag.rotation += angle;
+    let angle = match snap {
+        Some(increment) => snap_value(drag.rotation, increment),
+        None => drag.rotation,
     };
+    let rotation = Quat::from_axis_angle(axis, angle) * drag.start.rotation;
+    let rotation = limits.map_or(rotation, |limits| limits.clamp_rotation(rotation));
+    let translation = swing_about_pivot(drag, rotation, limits);
+
+    settings.propose(sel_entity, selection_transform_local, |proposal| {
+        proposal.rotation = rotation;
+        if let Some(translation) = translation {
+            proposal.translation = translation;
+        }
+    });
+    settings.is_dragging = true;
+}
 
-    // Calculate if and where the ray is hitting the Handle plane.
-    let Some(distance_delta) =
-        ray_delta.intersect_plane(gizmo_transform.translation(), InfinitePlane3d::new(direction_plane))
+/// Proposes rotating the selected Entity by the accumulated trackball rotation, in the space of its parent
+fn apply_free_rotation(
+    rotation: Quat,
+    q_local_transform: &Query<&Transform>,
+    q_constraints: &Query<&GizmoConstraints>,
+    q_limits: &Query<&GizmoLimits>,
+    settings: &mut TransformGizmoSettings,
+) {
+    let Some((sel_entity, selection_transform_local, drag, constraints, limits)) =
+        begin_drag(GizmoMode::Rotate, settings, q_local_transform, q_constraints, q_limits)
     else {
         return;
     };
-    let point_delta = ray_delta.get_point(distance_delta);
 
-    // Calculate the Effect of the mouse movement in the direction of the Handle
-    let origin = gizmo_transform.translation();
-    let origin_dir = gizmo_transform.back();
+    drag.free_rotation = (rotation * drag.free_rotation).normalize();
+    let delta = constraints.map_or(drag.free_rotation, |constraints| constraints.constrain_rotation(drag.free_rotation));
+    let rotation = delta * drag.start.rotation;
+    let rotation = limits.map_or(rotation, |limits| limits.clamp_rotation(rotation));
+    let translation = swing_about_pivot(drag, rotation, limits);
 
-    let dir1 = (point-origin).normalize();
-    let dir2 = (point_delta-origin).normalize();
+    settings.propose(sel_entity, selection_transform_local, |proposal| {
+        proposal.rotation = rotation;
+        if let Some(translation) = translation {
+            proposal.translation = translation;
+        }
+    });
+    settings.is_dragging = true;
+}
 
-    let angle_side = origin_dir.angle_between(dir1);
-    let angle_side_2 = origin_dir.angle_between(dir2);
+/// Proposes scaling the selected Entity by the accumulated drag, snapped to `snap` if given
+fn apply_scale(
+    delta: Vec3,
+    snap: Option<f32>,
+    q_local_transform: &Query<&Transform>,
+    q_constraints: &Query<&GizmoConstraints>,
+    q_limits: &Query<&GizmoLimits>,
+    settings: &mut TransformGizmoSettings,
+) {
+    let Some((sel_entity, selection_transform_local, drag, constraints, limits)) =
+        begin_drag(GizmoMode::Scale, settings, q_local_transform, q_constraints, q_limits)
+    else {
+        return;
+    };
 
-    let angle_diff = angle_side-angle_side_2;
+    drag.scale += constraints.map_or(delta, |constraints| constraints.constrain_scale(delta));
+    let offset = match snap {
+        Some(increment) => snap_vec3(drag.scale, increment),
+        None => drag.scale,
+    };
+    let scale = drag.start.scale * (Vec3::ONE + offset).max(Vec3::splat(MIN_SCALE_FACTOR));
+    let scale = limits.map_or(scale, |limits| limits.clamp_scale(scale));
+    // Move the Entity away from the pivot by the scale that was actually applied
+    let translation = drag.pivot.map(|pivot| {
+        let ratio = scale / drag.start.scale.max(Vec3::splat(f32::EPSILON));
+        let translation = pivot + (drag.start.translation - pivot) * ratio;
+        limits.map_or(translation, |limits| limits.clamp_translation(translation))
+    });
 
-    // Set the transformation
-    if let Ok(mut parent_transform_local) = q_local_transform.get_mut(parent_entity) {
-        parent_transform_local.rotate(Quat::from_axis_angle(axis_1, angle_diff));
-    } else {
-        log::warn!("TransformGizmo: Could not get Transform of Parent Entity: {parent_entity:?}");
-    }
+    settings.propose(sel_entity, selection_transform_local, |proposal| {
+        proposal.scale = scale;
+        if let Some(translation) = translation {
+            proposal.translation = translation;
+        }
+    });
+    settings.is_dragging = true;
+}
 
-    // Set the Transformation to the connected Object
-    if let Some(sel_entity) = settings.active_entity {
-        if let Ok(mut selection_transform_local) = q_local_transform.get_mut(sel_entity) {
-            selection_transform_local.rotate(Quat::from_axis_angle(axis_1, angle_diff));
-            settings.is_dragging = true;
-        } else {
-            log::warn!("TransformGizmo: Could not get Transform of selected Entity: {sel_entity:?}");
+/// This Observer Function stores the state of the selected Entity when a drag on a handle starts
+pub fn transform_drag_start(
+    drag: Trigger<Pointer<DragStart>>,
+    q_local_transform: Query<&Transform>,
+    q_camera: Query<(&Camera, &GlobalTransform), With<GizmoPickSource>>,
+    input_map: Res<GizmoInputMap>,
+    snapping: Res<GizmoSnapping>,
+    snappers: TranslationSnappers,
+    q_cursor: Query<&GlobalTransform, With<GizmoCursor>>,
+    q_parents: Query<&ChildOf>,
+    q_transform: Query<&GlobalTransform>,
+    mut settings: ResMut<TransformGizmoSettings>,
+    enabled: Res<GizmoEnabled>,
+    mut drag_started: EventWriter<GizmoDragStarted>,
+) {
+    if drag.button != input_map.drag_button || !enabled.0 {
+        return;
+    }
+    let Some(sel_entity) = settings.active_entity else {
+        return;
+    };
+    if let Ok(selection_transform_local) = q_local_transform.get(sel_entity) {
+        let mut gizmo_drag = GizmoDrag::new(*selection_transform_local);
+        // Snap a vertex of the Entity instead of its origin
+        let picks_vertex = snapping.vertex.is_some_and(|config| config.anchor == VertexAnchor::PickedVertex);
+        if let (true, Ok((camera, camera_transform))) = (picks_vertex, q_camera.single()) {
+            let cursor_position = viewport_position(camera, &drag.pointer_location);
+            if let Some(anchor) = snappers.vertex.pick_anchor(sel_entity, cursor_position, camera, camera_transform) {
+                gizmo_drag.anchor = anchor;
+            }
         }
+        if settings.pivot == GizmoPivot::Cursor {
+            gizmo_drag.pivot = cursor_in_parent_space(sel_entity, &q_cursor, &q_parents, &q_transform);
+        }
+        settings.drag = Some(gizmo_drag);
+        drag_started.write(GizmoDragStarted {
+            entity: sel_entity,
+            transform: *selection_transform_local,
+        });
+    } else {
+        log::warn!("TransformGizmo: Could not get Transform of selected Entity: {sel_entity:?}");
     }
 }
 
@@ -410,4 +787,110 @@ pub fn transform_drag_end(
     mut settings: ResMut<TransformGizmoSettings>,
 ) {
     settings.is_dragging = false;
+    settings.active_handle = None;
+    if let Some(drag) = settings.drag.as_mut() {
+        drag.finished = true;
+    }
+}
+
+/// This Observer Function remembers which handle is dragged, so it can be drawn in the active color
+pub fn mark_active_handle(
+    drag: Trigger<Pointer<DragStart>>,
+    input_map: Res<GizmoInputMap>,
+    enabled: Res<GizmoEnabled>,
+    mut settings: ResMut<TransformGizmoSettings>,
+) {
+    if drag.button == input_map.drag_button && enabled.0 {
+        settings.active_handle = Some(drag.target());
+    }
+}
+
+/// Sends [`GizmoDragEnded`] once the last proposal of a finished drag was committed
+pub fn finish_drag(
+    q_local_transform: Query<&Transform>,
+    mut settings: ResMut<TransformGizmoSettings>,
+    mut drag_ended: EventWriter<GizmoDragEnded>,
+) {
+    let Some(drag) = settings.drag.take_if(|drag| drag.finished) else {
+        return;
+    };
+    settings.rejected = false;
+    settings.snap_target = None;
+    settings.guides.clear();
+    if drag.cancelled {
+        return;
+    }
+    let Some(sel_entity) = settings.active_entity else {
+        return;
+    };
+    if let Ok(selection_transform_local) = q_local_transform.get(sel_entity) {
+        drag_ended.write(GizmoDragEnded {
+            entity: sel_entity,
+            start: drag.start,
+            end: *selection_transform_local,
+            cancelled: false,
+        });
+    }
+}
+
+/// Aborts the current drag when one of the [`GizmoInputMap::cancel`] keys is pressed
+pub fn cancel_drag(
+    keys: Res<ButtonInput<KeyCode>>,
+    input_map: Res<GizmoInputMap>,
+    mut settings: ResMut<TransformGizmoSettings>,
+    mut q_local_transform: Query<&mut Transform>,
+    mut drag_ended: EventWriter<GizmoDragEnded>,
+) {
+    if input_map.cancel_just_pressed(&keys) {
+        cancel_active_drag(&mut settings, &mut q_local_transform, &mut drag_ended);
+    }
+}
+
+/// Cancels the drag on a handle that is about to be despawned, and would never receive the end of the drag
+///
+/// The drag is finished right away, so [`finish_drag`] clears it. A finished drag is left to [`finish_drag`].
+pub(crate) fn abandon_active_drag(
+    settings: &mut TransformGizmoSettings,
+    q_local_transform: &mut Query<&mut Transform>,
+    drag_ended: &mut EventWriter<GizmoDragEnded>,
+) {
+    if settings.drag.as_ref().is_none_or(|drag| drag.finished) {
+        return;
+    }
+    cancel_active_drag(settings, q_local_transform, drag_ended);
+    if let Some(drag) = settings.drag.as_mut() {
+        drag.finished = true;
+    }
+}
+
+/// Cancels the current drag and restores the transform the selected Entity had when it started
+///
+/// The drag stays around as cancelled, so its remaining pointer events are ignored until it ends.
+pub(crate) fn cancel_active_drag(
+    settings: &mut TransformGizmoSettings,
+    q_local_transform: &mut Query<&mut Transform>,
+    drag_ended: &mut EventWriter<GizmoDragEnded>,
+) {
+    let Some(sel_entity) = settings.active_entity else {
+        return;
+    };
+    let Some(drag) = settings.drag.as_mut().filter(|drag| !drag.cancelled) else {
+        return;
+    };
+    drag.cancelled = true;
+    let start = drag.start;
+    settings.is_dragging = false;
+    settings.active_handle = None;
+    // The start transform was accepted once already, so it bypasses validation
+    settings.proposal = None;
+    settings.rejected = false;
+    if let Ok(mut selection_transform_local) = q_local_transform.get_mut(sel_entity) {
+        *selection_transform_local = start;
+    }
+    drag_ended.write(GizmoDragEnded {
+        entity: sel_entity,
+        start,
+        end: start,
+        cancelled: true,
+    });
 }
@@ -0,0 +1,91 @@
+use bevy::{ecs::system::SystemId, prelude::*};
+
+use crate::TransformGizmoSettings;
+
+/// A transform the gizmo wants to apply to the selected Entity.
+#[derive(Debug, Clone, Copy)]
+pub struct GizmoProposal {
+    pub entity: Entity,
+    /// Local transform the Entity has now.
+    pub current: Transform,
+    /// Local transform the gizmo wants to apply.
+    pub proposed: Transform,
+}
+
+/// Result of validating a [`GizmoProposal`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GizmoValidation {
+    /// Applies the proposed transform.
+    Accept,
+    /// Applies the given transform instead.
+    Modify(Transform),
+    /// Keeps the current transform and shows the rejection cue on the gizmo.
+    Reject,
+}
+
+/// One-shot system that validates every transform the gizmo proposes before it is applied.
+///
+/// ```ignore
+/// let validator = app.register_system(|In(proposal): In<GizmoProposal>| GizmoValidation::Accept);
+/// app.insert_resource(GizmoValidator(Some(validator)));
+/// ```
+#[derive(Resource, Debug, Default, Clone, Copy)]
+pub struct GizmoValidator(pub Option<SystemId<In<GizmoProposal>, GizmoValidation>>);
+
+/// Marks the cue shown on the gizmo while the last proposal was rejected.
+#[derive(Component)]
+pub struct GizmoRejectedIndicator;
+
+/// Runs the [`GizmoValidator`] on the pending proposal and applies the result.
+pub fn commit_proposal(world: &mut World) {
+    let proposal = world
+        .get_resource_mut::<TransformGizmoSettings>()
+        .and_then(|mut settings| settings.proposal.take());
+    let Some((entity, proposed)) = proposal else {
+        return;
+    };
+    let Some(current) = world.get::<Transform>(entity).copied() else {
+        log::warn!("TransformGizmo: Could not get Transform of selected Entity: {entity:?}");
+        return;
+    };
+
+    let validator = world.get_resource::<GizmoValidator>().and_then(|validator| validator.0);
+    let validation = match validator {
+        Some(system) => world
+            .run_system_with(system, GizmoProposal { entity, current, proposed })
+            .unwrap_or_else(|err| {
+                log::warn!("TransformGizmo: Could not run GizmoValidator: {err}");
+                GizmoValidation::Accept
+            }),
+        None => GizmoValidation::Accept,
+    };
+    let committed = match validation {
+        GizmoValidation::Accept => Some(proposed),
+        GizmoValidation::Modify(transform) => Some(transform),
+        GizmoValidation::Reject => None,
+    };
+
+    if let Some(mut settings) = world.get_resource_mut::<TransformGizmoSettings>() {
+        settings.rejected = committed.is_none();
+    }
+    let Some(committed) = committed else {
+        return;
+    };
+    if let Some(mut selection_transform_local) = world.get_mut::<Transform>(entity) {
+        *selection_transform_local = committed;
+    }
+}
+
+/// Shows the [`GizmoRejectedIndicator`] while the last proposal was rejected.
+pub fn update_rejected_indicator(
+    settings: Res<TransformGizmoSettings>,
+    mut q_indicator: Query<&mut Visibility, With<GizmoRejectedIndicator>>,
+) {
+    for mut visibility in &mut q_indicator {
+        visibility.set_if_neq(if settings.is_rejected() {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        });
+    }
+}